@@ -0,0 +1,87 @@
+//! Opt-in recording of mutating filesystem operations
+//!
+//! Mutating `…_anyhow` methods, such as [PathAnyhow::write_anyhow](crate::PathAnyhow::write_anyhow)
+//! or [PathAnyhow::remove_file_anyhow](crate::PathAnyhow::remove_file_anyhow), report each call
+//! to a thread-local recorder when one is active. Recording is enabled for the duration of a
+//! closure with [record]:
+//!
+//! ```
+//! use anyhow_std::{audit, PathAnyhow};
+//!
+//! let dir = std::env::temp_dir().join("anyhow-std-audit-doctest");
+//! let ((), records) = audit::record(|| {
+//!     dir.create_dir_all_anyhow().unwrap();
+//!     dir.remove_dir_anyhow().unwrap();
+//! });
+//!
+//! let ops: Vec<&str> = records.iter().map(|r| r.operation).collect();
+//! assert_eq!(ops, ["create_dir_all", "remove_dir"]);
+//! assert!(records.iter().all(|r| r.outcome.is_ok()));
+//! ```
+//!
+//! Operations on other threads are not recorded.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// A single mutating operation observed by [record]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// The name of the wrapped [std] operation, for example `"rename"`
+    pub operation: &'static str,
+    /// The path arguments of the operation, in argument order
+    pub paths: Vec<PathBuf>,
+    /// `Ok` on success, otherwise the full error description with context
+    pub outcome: Result<(), String>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Vec<Record>>> = const { RefCell::new(None) };
+}
+
+/// Call `f`, returning its result along with every mutating operation it performed on this thread
+///
+/// Calls may nest; an inner `record` captures only the operations within it and the outer
+/// recorder resumes afterwards.
+pub fn record<F, T>(f: F) -> (T, Vec<Record>)
+where
+    F: FnOnce() -> T,
+{
+    let outer = RECORDER.with(|r| r.borrow_mut().replace(vec![]));
+    let guard = Restore(outer);
+    let value = f();
+    let records = RECORDER.with(|r| r.borrow_mut().take()).unwrap_or_default();
+    drop(guard);
+    (value, records)
+}
+
+/// Restores the enclosing recorder, even if the recorded closure panics
+struct Restore(Option<Vec<Record>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        RECORDER.with(|r| *r.borrow_mut() = outer);
+    }
+}
+
+/// Run `f` and report its outcome to the active recorder, if any
+pub(crate) fn audited<F, T>(operation: &'static str, paths: &[&Path], f: F) -> anyhow::Result<T>
+where
+    F: FnOnce() -> anyhow::Result<T>,
+{
+    let res = f();
+    RECORDER.with(|r| {
+        if let Some(records) = r.borrow_mut().as_mut() {
+            records.push(Record {
+                operation,
+                paths: paths.iter().map(|p| p.to_path_buf()).collect(),
+                outcome: res.as_ref().map(|_| ()).map_err(|e| format!("{:#}", e)),
+            });
+        }
+    });
+    res
+}
+
+#[cfg(test)]
+mod tests;
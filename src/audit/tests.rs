@@ -0,0 +1,81 @@
+use crate::audit::{record, Record};
+use crate::PathAnyhow;
+use std::path::Path;
+
+#[test]
+fn records_mutations_only() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let a = dir.path().join("a");
+    let b = dir.path().join("b");
+
+    let (res, records) = record(|| -> anyhow::Result<()> {
+        a.write_anyhow("hello")?;
+        a.read_to_string_anyhow()?;
+        a.rename_anyhow(&b)?;
+        b.remove_file_anyhow()?;
+        Ok(())
+    });
+    res?;
+
+    assert_eq!(
+        records,
+        vec![
+            Record {
+                operation: "write",
+                paths: vec![a.clone()],
+                outcome: Ok(()),
+            },
+            Record {
+                operation: "rename",
+                paths: vec![a.clone(), b.clone()],
+                outcome: Ok(()),
+            },
+            Record {
+                operation: "remove_file",
+                paths: vec![b.clone()],
+                outcome: Ok(()),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn records_failure_outcome() {
+    let path = Path::new("/this/path/should/not/exist");
+    let (res, records) = record(|| path.remove_dir_anyhow());
+    assert!(res.is_err());
+    assert_eq!(
+        records,
+        vec![Record {
+            operation: "remove_dir",
+            paths: vec![path.to_path_buf()],
+            outcome: Err(
+                r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+                    .to_string()
+            ),
+        }]
+    );
+}
+
+#[test]
+fn nested_and_inactive() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let a = dir.path().join("a");
+    let b = dir.path().join("b");
+
+    // Not recorded: no recorder is active.
+    a.create_dir_anyhow()?;
+
+    let ((inner, res), outer) = record(|| {
+        let (res, inner) = record(|| b.create_dir_anyhow());
+        a.remove_dir_anyhow().unwrap();
+        (inner, res)
+    });
+    res?;
+
+    let ops = |rs: &[Record]| rs.iter().map(|r| r.operation).collect::<Vec<_>>();
+    assert_eq!(ops(&inner), ["create_dir"]);
+    assert_eq!(ops(&outer), ["remove_dir"]);
+    Ok(())
+}
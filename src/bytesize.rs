@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Display a byte count in human-readable binary units, as used in size-related error contexts
+///
+/// ```
+/// use anyhow_std::ByteSize;
+///
+/// assert_eq!(ByteSize(512).to_string(), "512 B");
+/// assert_eq!(ByteSize(100 * 1024 * 1024).to_string(), "100 MiB");
+/// assert_eq!(ByteSize(3_650_722_201).to_string(), "3.4 GiB");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, derive_more::From)]
+pub struct ByteSize(pub u64);
+
+const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        // Compare after rounding, so that e.g. 1023.96 KiB is shown as 1 MiB, not 1024 KiB.
+        while format!("{:.1}", value).parse::<f64>().unwrap_or(value) >= 1024.0
+            && unit + 1 < UNITS.len()
+        {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        let rendered = format!("{:.1}", value);
+        let rendered = rendered.strip_suffix(".0").unwrap_or(&rendered);
+        write!(f, "{} {}", rendered, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests;
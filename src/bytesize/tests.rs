@@ -0,0 +1,17 @@
+use crate::ByteSize;
+use test_case::test_case;
+
+#[test_case(0 => "0 B")]
+#[test_case(1023 => "1023 B")]
+#[test_case(1024 => "1 KiB")]
+#[test_case(1536 => "1.5 KiB")]
+#[test_case(1024 * 1024 - 1 => "1 MiB")]
+#[test_case(1024 * 1024 - 51 => "1 MiB")]
+#[test_case(1024 * 1024 - 52 => "1023.9 KiB")]
+#[test_case(1024 * 1024 * 1024 - 1 => "1 GiB")]
+#[test_case(100 * 1024 * 1024 => "100 MiB")]
+#[test_case(3_650_722_201 => "3.4 GiB")]
+#[test_case(u64::MAX => "16 EiB")]
+fn display(n: u64) -> String {
+    ByteSize(n).to_string()
+}
@@ -0,0 +1,135 @@
+//! Cooperative cancellation of long-running helpers
+//!
+//! A [CancelToken] is shared with whatever decides to stop the work, such as a Ctrl-C handler,
+//! and the work runs inside [CancelToken::run]. Helpers which traverse directory trees or
+//! download files check the token on the calling thread as they go, and once it is cancelled
+//! they return a [Cancelled] error describing how far they got:
+//!
+//! ```
+//! use anyhow_std::cancel::{CancelToken, Cancelled};
+//! use anyhow_std::PathAnyhow;
+//!
+//! let token = CancelToken::new();
+//! token.cancel();
+//! let dir = std::env::temp_dir();
+//! let err = token.run(|| dir.largest_entries_anyhow(1)).err().unwrap();
+//! assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Entries(0)));
+//! ```
+//!
+//! Helpers run outside [CancelToken::run] are never cancelled.
+
+use crate::ByteSize;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared between threads requesting that work stop
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+impl CancelToken {
+    /// Create a token which is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of all work this token was given to
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Return `true` once [CancelToken::cancel] has been called on any clone of this token
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// Call `f` with this token checked by the helpers it calls on this thread
+    ///
+    /// Calls may nest; the innermost token applies and the outer one resumes afterwards.
+    pub fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let outer = CURRENT.with(|c| c.borrow_mut().replace(self.clone()));
+        let _guard = Restore(outer);
+        f()
+    }
+}
+
+/// Restores the enclosing token, even if the closure panics
+struct Restore(Option<CancelToken>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        CURRENT.with(|c| *c.borrow_mut() = outer);
+    }
+}
+
+/// The error returned by a helper which stopped because its [CancelToken] was cancelled
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Cancelled {
+    /// Cancelled after processing this many entries
+    Entries(u64),
+    /// Cancelled after transferring this many bytes
+    Bytes(u64),
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cancelled::Entries(n) => write!(
+                f,
+                "operation cancelled after {} entr{}",
+                n,
+                if *n == 1 { "y" } else { "ies" }
+            ),
+            Cancelled::Bytes(n) => write!(f, "operation cancelled after {}", ByteSize(*n)),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+fn current_cancelled() -> bool {
+    CURRENT.with(|c| c.borrow().as_ref().is_some_and(CancelToken::is_cancelled))
+}
+
+/// Fail with [Cancelled::Entries] if the current token is cancelled
+pub(crate) fn check_entries(entries: u64) -> anyhow::Result<()> {
+    if current_cancelled() {
+        Err(Cancelled::Entries(entries).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Fail with [Cancelled::Bytes] if the current token is cancelled
+#[cfg_attr(not(feature = "ureq"), allow(dead_code))]
+pub(crate) fn check_bytes(bytes: u64) -> anyhow::Result<()> {
+    if current_cancelled() {
+        Err(Cancelled::Bytes(bytes).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Return a [Cancelled] error as the outer error, so bulk helpers which collect per-entry errors
+/// stop on it instead
+pub(crate) fn unless_cancelled<T>(res: anyhow::Result<T>) -> anyhow::Result<anyhow::Result<T>> {
+    match res {
+        Err(e) if e.is::<Cancelled>() => Err(e),
+        res => Ok(res),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,36 @@
+use crate::cancel::{CancelToken, Cancelled};
+use crate::PathAnyhow;
+use test_case::test_case;
+
+#[test_case(Cancelled::Entries(1) => "operation cancelled after 1 entry"; "one entry")]
+#[test_case(Cancelled::Entries(3) => "operation cancelled after 3 entries"; "entries")]
+#[test_case(Cancelled::Bytes(3 * 1024 * 1024) => "operation cancelled after 3 MiB"; "bytes")]
+fn display(c: Cancelled) -> String {
+    c.to_string()
+}
+
+#[test]
+fn walk_stops_when_cancelled() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    for name in ["a", "b", "c"] {
+        dir.path().join(name).write_anyhow("x")?;
+    }
+
+    let token = CancelToken::new();
+    assert_eq!(token.run(|| dir.path().largest_entries_anyhow(1))?.len(), 1);
+
+    token.cancel();
+    assert!(token.clone().is_cancelled());
+    let err = token
+        .run(|| dir.path().largest_entries_anyhow(1))
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.downcast_ref::<Cancelled>(),
+        Some(&Cancelled::Entries(0))
+    );
+
+    // Outside `run` the token has no effect.
+    assert_eq!(dir.path().largest_entries_anyhow(1)?.len(), 1);
+    Ok(())
+}
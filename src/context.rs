@@ -0,0 +1,374 @@
+//! The context-building blocks used throughout this crate
+//!
+//! These helpers let callers wrap [std] APIs this crate does not cover yet while producing error
+//! messages consistent with the crate's own.
+//!
+//! # Message Grammar
+//!
+//! Contexts follow a small grammar so that log parsers can rely on their shape. Paths are always
+//! rendered with [escape_os], i.e. quoted and escaped:
+//!
+//! - Single-path operations: `while processing path "<path>"`
+//! - Two-path operations: `while <operation> path "<from>" to "<to>"`, where `<operation>` is
+//!   the [Operation::verb], e.g. `while copying path "a" to "b"`
+//! - Values an operation was given are inner contexts: `with <name> <value>`, for example
+//!   `while processing path "a": with prefix "/b"`
+//!
+//! ```
+//! use anyhow_std::context::{with_path_context, with_two_path_context, Operation};
+//! use std::path::Path;
+//!
+//! let path = Path::new("/this/path/should/not/exist");
+//! let res = with_path_context(std::fs::read(path), path);
+//! assert_eq!(
+//!     format!("{:#}", res.err().unwrap()),
+//!     r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+//! );
+//!
+//! # #[cfg(not(feature = "compat-messages"))]
+//! # {
+//! let to = Path::new("/this/path/also/should/not/exist");
+//! let res = with_two_path_context(std::fs::copy(path, to), Operation::Copy, path, to);
+//! assert_eq!(
+//!     format!("{:#}", res.err().unwrap()),
+//!     r#"while copying path "/this/path/should/not/exist" to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#,
+//! );
+//! # }
+//! ```
+//!
+//! The templates are implemented only here: [with_path_context], [with_two_path_context], and
+//! [value_context]. The remaining contexts of this crate name neither a path nor a value an
+//! operation was given, describe a step instead, and are written where they are added:
+//!
+//! - `while reading directory "<path>"`, on entries of [ReadDir](crate::fs::ReadDir)
+//! - `while changing current directory from "<a>" to "<b>"`, prefixed with `while restoring`
+//!   when a [CurrentDirGuard](crate::fs::CurrentDirGuard) restores it
+//! - `environment variable "<key>"` and `while setting environment variable "<key>"`, in
+//!   [env](crate::env)
+//! - `while processing os string "<s>"`, from [OsStrAnyhow](crate::OsStrAnyhow)
+//! - `command: …`, `status: <code>`, `while validating command program "<program>"`,
+//!   `in pre-exec hook "<label>"`, and `while running editor "<editor>" on "<path>"`, in
+//!   [process](crate::process)
+//! - `after backing up to "<backup>"` and `with no existing file to back up`, from
+//!   [PathAnyhow::write_with_backup_anyhow](crate::PathAnyhow::write_with_backup_anyhow)
+//! - steps such as `while connecting`, `while reading the current directory`, or `while reading
+//!   the password`
+//!
+//! Path contexts are attached as [PathContext] values, so tools can inspect them with
+//! [anyhow::Error::downcast_ref] instead of parsing the rendered messages.
+//!
+//! ## Stability
+//!
+//! The grammar is versioned by [MESSAGE_FORMAT_VERSION]. Within one version, the shape of each
+//! context above is kept across releases, so snapshot tests of error messages stay valid; the
+//! version is bumped, in a release noted as breaking, whenever a template changes. The messages
+//! of wrapped [std] errors are outside this guarantee, as is any message that does not follow the
+//! grammar.
+//!
+//! ## Relative Paths
+//!
+//! Tools that work inside one project tree can shorten messages with [set_base_dir]: paths
+//! inside the base directory are then rendered relative to it, e.g. `while processing path
+//! "src/main.rs"`, while paths outside it are rendered unchanged. Only the rendering is affected:
+//! a [PathContext] and the records of [audit](crate::audit) keep the paths as given, and a path
+//! context is rendered with the base directory set when the error is displayed.
+//!
+//! ## Translations
+//!
+//! With the `i18n` feature, the three templates are looked up in the `catalog` registered at
+//! startup, so applications can translate them, as are the directory-reading and
+//! current-directory contexts. The other contexts listed above are always in English.
+//!
+//! ## Compatibility
+//!
+//! Enabling the `compat-messages` feature restores the messages of earlier releases, where
+//! copying, renaming, and hard-linking rendered as `while processing path "<from>": with <arg>
+//! "<to>"`, with `<arg>` one of `copy_to`, `rename_to`, or `link_to`, and
+//! [PathAnyhow::write_anyhow] rendered as `while writing to "<path>"`. Two-path operations added
+//! since, which have no earlier message, follow the same shape with `backup_to`, `move_to`, or
+//! `compress_to`.
+//!
+//! [PathAnyhow::write_anyhow]: crate::PathAnyhow::write_anyhow
+
+#[cfg(feature = "i18n")]
+pub mod catalog;
+
+use crate::escape_os;
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// The version of the [message grammar](self#message-grammar)
+pub const MESSAGE_FORMAT_VERSION: u32 = 1;
+
+static BASE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Render paths in error contexts relative to `dir`, or as given if `dir` is `None`
+///
+/// The setting is process-wide and returns the previous base directory. Paths are compared
+/// lexically, so `dir` should be spelled the way paths passed to this crate are, typically
+/// absolute.
+pub fn set_base_dir<P>(dir: Option<P>) -> Option<PathBuf>
+where
+    P: Into<PathBuf>,
+{
+    let mut base = BASE_DIR.write().unwrap_or_else(|e| e.into_inner());
+    std::mem::replace(&mut *base, dir.map(Into::into))
+}
+
+/// The base directory set by [set_base_dir], if any
+pub fn base_dir() -> Option<PathBuf> {
+    BASE_DIR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Render `path` for an error context: relative to the [base_dir] when inside it, then quoted and
+/// escaped with [escape_os]
+pub fn render_path(path: &Path) -> String {
+    let base = BASE_DIR.read().unwrap_or_else(|e| e.into_inner());
+    match base.as_deref().and_then(|b| path.strip_prefix(b).ok()) {
+        Some(rel) if rel.as_os_str().is_empty() => escape_os("."),
+        Some(rel) => escape_os(rel),
+        None => escape_os(path),
+    }
+}
+
+/// An operation involving two paths, used to phrase two-path error contexts
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Copying file contents, as in [std::fs::copy]
+    Copy,
+    /// Renaming, as in [std::fs::rename]
+    Rename,
+    /// Hard-linking, as in [std::fs::hard_link]
+    HardLink,
+    /// Backing up a file before overwriting it, as in
+    /// [PathAnyhow::write_with_backup_anyhow](crate::PathAnyhow::write_with_backup_anyhow)
+    Backup,
+    /// Moving, as in [PathAnyhow::move_anyhow](crate::PathAnyhow::move_anyhow)
+    Move,
+    /// Gzip-compressing a file into another, as `PathAnyhow::rotate_compressed_anyhow` does with
+    /// the `flate2` feature
+    Compress,
+}
+
+impl Operation {
+    /// The verb used in error contexts, for example `"copying"`
+    pub fn verb(self) -> &'static str {
+        match self {
+            Operation::Copy => "copying",
+            Operation::Rename => "renaming",
+            Operation::HardLink => "hard-linking",
+            Operation::Backup => "backing up",
+            Operation::Move => "moving",
+            Operation::Compress => "compressing",
+        }
+    }
+
+    #[cfg(feature = "compat-messages")]
+    fn compat_arg(self) -> &'static str {
+        match self {
+            Operation::Copy => "copy_to",
+            Operation::Rename => "rename_to",
+            Operation::HardLink => "link_to",
+            Operation::Backup => "backup_to",
+            Operation::Move => "move_to",
+            Operation::Compress => "compress_to",
+        }
+    }
+}
+
+/// The typed context added by [with_path_context] and [with_two_path_context]
+///
+/// It renders as the single-path or two-path context of the [message grammar](self#message-grammar),
+/// and tools can recover it with [anyhow::Error::downcast_ref] rather than parse messages:
+///
+/// ```
+/// use anyhow_std::context::{with_path_context, PathContext};
+/// use std::path::Path;
+///
+/// let path = Path::new("/this/path/should/not/exist");
+/// let err = with_path_context(std::fs::read(path), path).err().unwrap();
+/// let ctx = err.downcast_ref::<PathContext>().unwrap();
+/// assert_eq!(ctx.operation(), None);
+/// assert_eq!(ctx.paths(), [path]);
+/// ```
+///
+/// With the `compat-messages` feature, two-path operations add a single-path context for their
+/// source instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathContext {
+    operation: Option<Operation>,
+    paths: Vec<PathBuf>,
+}
+
+impl PathContext {
+    /// The two-path operation, or `None` for a single-path context
+    pub fn operation(&self) -> Option<Operation> {
+        self.operation
+    }
+
+    /// The processed path, or the source and destination of a two-path operation, as given rather
+    /// than relative to the [base_dir]
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+impl std::fmt::Display for PathContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths: Vec<String> = self.paths.iter().map(|p| render_path(p)).collect();
+        let msg = match (self.operation, paths.as_slice()) {
+            (Some(operation), [from, to]) => two_path_message(operation, from, to),
+            (_, [path, ..]) => path_message(path),
+            (_, []) => unreachable!("a path context without paths"),
+        };
+        f.write_str(&msg)
+    }
+}
+
+fn path_message(path: &str) -> String {
+    #[cfg(not(feature = "i18n"))]
+    {
+        format!("while processing path {}", path)
+    }
+
+    #[cfg(feature = "i18n")]
+    {
+        catalog::catalog().path(path)
+    }
+}
+
+fn two_path_message(operation: Operation, from: &str, to: &str) -> String {
+    #[cfg(not(feature = "i18n"))]
+    {
+        format!("while {} path {} to {}", operation.verb(), from, to)
+    }
+
+    #[cfg(feature = "i18n")]
+    {
+        catalog::catalog().two_path(operation, from, to)
+    }
+}
+
+/// Add `path` as error context, as single-path methods such as
+/// [PathAnyhow::read_anyhow](crate::PathAnyhow::read_anyhow) do
+pub fn with_path_context<T, E>(res: Result<T, E>, path: &Path) -> anyhow::Result<T>
+where
+    Result<T, E>: Context<T, E>,
+{
+    res.with_context(|| PathContext {
+        operation: None,
+        paths: vec![path.to_path_buf()],
+    })
+}
+
+/// Add the paths of the two-path `operation` from `from` to `to` as error context, as methods
+/// such as [PathAnyhow::copy_anyhow](crate::PathAnyhow::copy_anyhow) do
+pub fn with_two_path_context<T, E>(
+    res: Result<T, E>,
+    operation: Operation,
+    from: &Path,
+    to: &Path,
+) -> anyhow::Result<T>
+where
+    Result<T, E>: Context<T, E>,
+{
+    #[cfg(not(feature = "compat-messages"))]
+    {
+        res.with_context(|| PathContext {
+            operation: Some(operation),
+            paths: vec![from.to_path_buf(), to.to_path_buf()],
+        })
+    }
+
+    #[cfg(feature = "compat-messages")]
+    {
+        with_path_context::<T, anyhow::Error>(
+            res.with_context(|| value_context(operation.compat_arg(), render_path(to))),
+            from,
+        )
+    }
+}
+
+/// Render the value context `with <name> <value>` describing a value an operation was given
+///
+/// ```
+/// use anyhow::Context;
+/// use anyhow_std::context::value_context;
+///
+/// let res: anyhow::Result<()> = Err(anyhow::anyhow!("failed")).context(value_context("mode", "0o750"));
+/// assert_eq!(format!("{:#}", res.err().unwrap()), "with mode 0o750: failed");
+/// ```
+pub fn value_context<V>(name: &str, value: V) -> String
+where
+    V: std::fmt::Display,
+{
+    #[cfg(not(feature = "i18n"))]
+    {
+        format!("with {} {}", name, value)
+    }
+
+    #[cfg(feature = "i18n")]
+    {
+        catalog::catalog().value(name, &value.to_string())
+    }
+}
+
+/// Render the context of reading the entries of the directory `path`
+pub(crate) fn read_dir_context(path: &Path) -> String {
+    #[cfg(not(feature = "i18n"))]
+    {
+        format!("while reading directory {}", render_path(path))
+    }
+
+    #[cfg(feature = "i18n")]
+    {
+        catalog::catalog().read_dir(&render_path(path))
+    }
+}
+
+/// Render the context of changing the current directory from `from` to `to`, or of restoring
+/// `to` as the previous current directory
+pub(crate) fn change_dir_context(from: &Path, to: &Path, restoring: bool) -> String {
+    let (from, to) = (render_path(from), render_path(to));
+
+    #[cfg(not(feature = "i18n"))]
+    {
+        let msg = format!("while changing current directory from {} to {}", from, to);
+        if restoring {
+            format!("while restoring {}", msg)
+        } else {
+            msg
+        }
+    }
+
+    #[cfg(feature = "i18n")]
+    {
+        let catalog = catalog::catalog();
+        if restoring {
+            catalog.restore_dir(&from, &to)
+        } else {
+            catalog.change_dir(&from, &to)
+        }
+    }
+}
+
+/// Add the context of [PathAnyhow::write_anyhow](crate::PathAnyhow::write_anyhow)
+pub(crate) fn with_write_context<T, E>(res: Result<T, E>, path: &Path) -> anyhow::Result<T>
+where
+    Result<T, E>: Context<T, E>,
+{
+    #[cfg(not(feature = "compat-messages"))]
+    {
+        with_path_context(res, path)
+    }
+
+    #[cfg(feature = "compat-messages")]
+    {
+        res.with_context(|| format!("while writing to {}", render_path(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,94 @@
+//! Translatable templates for the path, two-path, and value contexts
+//!
+//! This module requires the `i18n` feature. An application registers its [Catalog] once at
+//! startup with [set_catalog]; until then, and for any template a catalog does not override, the
+//! [English] defaults are used. When the `compat-messages` feature is also enabled, two-path
+//! contexts keep their compatibility rendering, a path context with the destination as a value
+//! context, and never use [Catalog::two_path].
+//!
+//! Besides the templates, only the contexts of reading a directory and of changing the current
+//! directory are in the catalog. The other step contexts [listed](crate::context#message-grammar)
+//! with the grammar, such as `environment variable "<key>"` or `command: …`, are not translated.
+//!
+//! ```
+//! use anyhow_std::context::catalog::{set_catalog, Catalog};
+//!
+//! struct German;
+//!
+//! impl Catalog for German {
+//!     fn path(&self, path: &str) -> String {
+//!         format!("beim Verarbeiten des Pfads {}", path)
+//!     }
+//! }
+//!
+//! set_catalog(&German).unwrap();
+//! ```
+
+use crate::context::Operation;
+use std::sync::OnceLock;
+
+static CATALOG: OnceLock<&'static dyn Catalog> = OnceLock::new();
+
+/// The templates of the [message grammar](crate::context#message-grammar)
+///
+/// Paths are passed already quoted and escaped, as rendered by
+/// [render_path](crate::context::render_path). Every method defaults to the English template.
+pub trait Catalog: Send + Sync {
+    /// The single-path context, by default `while processing path <path>`
+    fn path(&self, path: &str) -> String {
+        format!("while processing path {}", path)
+    }
+
+    /// The two-path context of `operation`, by default `while <verb> path <from> to <to>`
+    fn two_path(&self, operation: Operation, from: &str, to: &str) -> String {
+        format!("while {} path {} to {}", operation.verb(), from, to)
+    }
+
+    /// The value context, by default `with <name> <value>`
+    ///
+    /// `name` is the English name of the value, such as `mode`, and `value` is already rendered.
+    fn value(&self, name: &str, value: &str) -> String {
+        format!("with {} {}", name, value)
+    }
+
+    /// The context of reading the entries of a directory, by default
+    /// `while reading directory <path>`
+    fn read_dir(&self, path: &str) -> String {
+        format!("while reading directory {}", path)
+    }
+
+    /// The context of changing the current directory, by default
+    /// `while changing current directory from <from> to <to>`
+    fn change_dir(&self, from: &str, to: &str) -> String {
+        format!("while changing current directory from {} to {}", from, to)
+    }
+
+    /// The context of restoring the previous current directory `to`, by default
+    /// `while restoring ` followed by [Catalog::change_dir]
+    fn restore_dir(&self, from: &str, to: &str) -> String {
+        format!("while restoring {}", self.change_dir(from, to))
+    }
+}
+
+/// The built-in English catalog
+#[derive(Copy, Clone, Debug, Default)]
+pub struct English;
+
+impl Catalog for English {}
+
+/// Register `catalog` for all subsequent error contexts
+///
+/// The catalog can be set only once per process; later calls fail.
+pub fn set_catalog(catalog: &'static dyn Catalog) -> anyhow::Result<()> {
+    CATALOG
+        .set(catalog)
+        .map_err(|_| anyhow::anyhow!("a message catalog is already set"))
+}
+
+/// The registered catalog, or [English] if none is set
+pub fn catalog() -> &'static dyn Catalog {
+    CATALOG.get().copied().unwrap_or(&English)
+}
+
+#[cfg(test)]
+mod tests;
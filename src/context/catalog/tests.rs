@@ -0,0 +1,75 @@
+use crate::context::catalog::{catalog, set_catalog, Catalog, English};
+use crate::context::Operation;
+use crate::PathAnyhow;
+use std::path::Path;
+
+/// Translates only paths containing a marker, since the catalog is process-wide and other tests
+/// run concurrently
+struct Marked;
+
+const MARKER: &str = "anyhow-std-catalog-test";
+
+impl Catalog for Marked {
+    fn path(&self, path: &str) -> String {
+        if path.contains(MARKER) {
+            format!("beim Verarbeiten des Pfads {}", path)
+        } else {
+            English.path(path)
+        }
+    }
+
+    fn value(&self, name: &str, value: &str) -> String {
+        if value.contains(MARKER) {
+            format!("mit {} {}", name, value)
+        } else {
+            English.value(name, value)
+        }
+    }
+}
+
+#[test]
+fn english_defaults() {
+    assert_eq!(English.path(r#""a""#), r#"while processing path "a""#);
+    assert_eq!(
+        English.two_path(Operation::HardLink, r#""a""#, r#""b""#),
+        r#"while hard-linking path "a" to "b""#
+    );
+    assert_eq!(English.value("mode", "0o750"), "with mode 0o750");
+    assert_eq!(English.read_dir(r#""a""#), r#"while reading directory "a""#);
+    assert_eq!(
+        English.restore_dir(r#""a""#, r#""b""#),
+        r#"while restoring while changing current directory from "a" to "b""#
+    );
+}
+
+#[test]
+fn registered_catalog() {
+    set_catalog(&Marked).unwrap();
+    assert_eq!(
+        format!("{:#}", set_catalog(&English).err().unwrap()),
+        "a message catalog is already set"
+    );
+
+    let path = Path::new("/this/path/should/not/exist").join(MARKER);
+    assert_eq!(
+        format!("{:#}", path.read_anyhow().err().unwrap()),
+        format!(
+            r#"beim Verarbeiten des Pfads "{}": No such file or directory (os error 2)"#,
+            path.display()
+        )
+    );
+    assert_eq!(
+        format!(
+            "{:#}",
+            Path::new("/a")
+                .strip_prefix_anyhow(Path::new("/b").join(MARKER))
+                .err()
+                .unwrap()
+        ),
+        format!(
+            r#"while processing path "/a": mit prefix "/b/{}": prefix not found"#,
+            MARKER
+        )
+    );
+    assert_eq!(catalog().path(r#""a""#), r#"while processing path "a""#);
+}
@@ -0,0 +1,104 @@
+use crate::PathAnyhow;
+use std::path::Path;
+use test_case::test_case;
+
+#[cfg_attr(
+    not(feature = "compat-messages"),
+    test_case(Path::copy_anyhow::<&str> => r#"while copying path "/this/path/should/not/exist" to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "copy"),
+    test_case(Path::rename_anyhow::<&str> => r#"while renaming path "/this/path/should/not/exist" to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "rename"),
+    test_case(Path::hard_link_anyhow::<&str> => r#"while hard-linking path "/this/path/should/not/exist" to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "hard link"),
+)]
+#[cfg_attr(
+    feature = "compat-messages",
+    test_case(Path::copy_anyhow::<&str> => r#"while processing path "/this/path/should/not/exist": with copy_to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "copy"),
+    test_case(Path::rename_anyhow::<&str> => r#"while processing path "/this/path/should/not/exist": with rename_to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "rename"),
+    test_case(Path::hard_link_anyhow::<&str> => r#"while processing path "/this/path/should/not/exist": with link_to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#; "hard link"),
+)]
+fn two_path_grammar<F, T>(method: F) -> String
+where
+    F: FnOnce(&Path, &'static str) -> anyhow::Result<T>,
+{
+    let err = method(
+        Path::new("/this/path/should/not/exist"),
+        "/this/path/also/should/not/exist",
+    )
+    .err()
+    .unwrap();
+    format!("{:#}", err)
+}
+
+#[test]
+fn base_dir_relative_rendering() -> anyhow::Result<()> {
+    use crate::context::{base_dir, render_path, set_base_dir};
+
+    let dir = tempfile::TempDir::new()?;
+    let missing = dir.path().join("src").join("main.rs");
+
+    // Other tests run concurrently, so only paths inside this unique directory are affected.
+    let previous = set_base_dir(Some(dir.path()));
+    let err = missing.read_anyhow().err().unwrap();
+    let rendered = format!("{:#}", err);
+    let stripped = dir
+        .path()
+        .join("src")
+        .strip_prefix_anyhow(dir.path().join("lib"))
+        .err()
+        .unwrap();
+    let stripped = format!("{:#}", stripped);
+    let same = render_path(dir.path());
+    let outside = render_path(Path::new("/elsewhere"));
+    assert_eq!(base_dir().as_deref(), Some(dir.path()));
+    set_base_dir(previous);
+
+    assert_eq!(
+        rendered,
+        r#"while processing path "src/main.rs": No such file or directory (os error 2)"#
+    );
+    assert_eq!(
+        stripped,
+        r#"while processing path "src": with prefix "lib": prefix not found"#
+    );
+    // The typed context keeps the full path, which is rendered relative only when displayed.
+    assert_eq!(
+        err.downcast_ref::<crate::context::PathContext>()
+            .unwrap()
+            .paths(),
+        std::slice::from_ref(&missing)
+    );
+    assert_eq!(
+        err.to_string(),
+        format!("while processing path {}", crate::escape_os(&missing))
+    );
+    assert_eq!(same, r#"".""#);
+    assert_eq!(outside, r#""/elsewhere""#);
+    assert_eq!(render_path(&missing), crate::escape_os(&missing));
+    Ok(())
+}
+
+#[test]
+fn typed_path_context() {
+    use crate::context::PathContext;
+
+    let err = Path::new("/this/path/should/not/exist")
+        .copy_anyhow("/this/path/also/should/not/exist")
+        .err()
+        .unwrap();
+    let ctx = err.downcast_ref::<PathContext>().unwrap();
+    #[cfg(not(feature = "compat-messages"))]
+    {
+        assert_eq!(ctx.operation(), Some(crate::context::Operation::Copy));
+        assert_eq!(
+            ctx.paths(),
+            [
+                Path::new("/this/path/should/not/exist"),
+                Path::new("/this/path/also/should/not/exist")
+            ]
+        );
+    }
+    #[cfg(feature = "compat-messages")]
+    {
+        assert_eq!(ctx.operation(), None);
+        assert_eq!(ctx.paths(), [Path::new("/this/path/should/not/exist")]);
+    }
+    assert_eq!(err.to_string(), ctx.to_string());
+}
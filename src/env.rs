@@ -2,6 +2,12 @@
 use anyhow::{anyhow, Context};
 use std::ffi::{OsStr, OsString};
 
+#[cfg(feature = "serde")]
+mod config;
+
+#[cfg(feature = "serde")]
+pub use self::config::from_env_anyhow;
+
 /// Wrap [std::env::var] providing `key` in error context
 pub fn var<K>(key: K) -> anyhow::Result<String>
 where
@@ -25,14 +31,86 @@ fn var_os_without_context<K>(key: K) -> anyhow::Result<OsString>
 where
     K: AsRef<OsStr>,
 {
-    let lossy_cow = key.as_ref().to_string_lossy();
+    check_forbidden(key.as_ref(), &['=', '\0'])?;
+    std::env::var_os(key).ok_or_else(|| anyhow!("environment variable not found"))
+}
+
+fn check_forbidden(s: &OsStr, forbidden: &[char]) -> anyhow::Result<()> {
+    let lossy_cow = s.to_string_lossy();
     let lossy_str = lossy_cow.as_ref();
-    for c in ['=', '\0'] {
+    for &c in forbidden {
         if lossy_str.contains(c) {
             return Err(anyhow!("environment variable contains {:?}", c));
         }
     }
-    std::env::var_os(key).ok_or_else(|| anyhow!("environment variable not found"))
+    Ok(())
+}
+
+/// Set or, for `None` values, remove each of `vars`, call `f`, then restore the previous values
+///
+/// Previous values are restored even if `f` returns an error or panics. Every key and value is
+/// validated before any variable is changed, and errors name the offending variable. The
+/// environment is shared by the whole process, so tests using this should not run concurrently
+/// with others reading the same variables.
+pub fn with_vars_anyhow<I, K, V, F, T>(vars: I, f: F) -> anyhow::Result<T>
+where
+    I: IntoIterator<Item = (K, Option<V>)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+    F: FnOnce() -> anyhow::Result<T>,
+{
+    let vars: Vec<(OsString, Option<OsString>)> = vars
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                k.as_ref().to_os_string(),
+                v.map(|v| v.as_ref().to_os_string()),
+            )
+        })
+        .collect();
+
+    for (key, value) in &vars {
+        let res = if key.is_empty() {
+            Err(anyhow!("environment variable is empty"))
+        } else {
+            check_forbidden(key, &['=', '\0'])
+        };
+        res.and_then(|()| match value {
+            Some(value) => check_forbidden(value, &['\0']).context("in value"),
+            None => Ok(()),
+        })
+        .with_context(|| {
+            format!(
+                "while setting environment variable {:?}",
+                key.to_string_lossy()
+            )
+        })?;
+    }
+
+    let mut guard = RestoreVars(vec![]);
+    for (key, value) in vars {
+        guard.0.push((key.clone(), std::env::var_os(&key)));
+        set_or_remove(&key, value.as_deref());
+    }
+    f()
+}
+
+fn set_or_remove(key: &OsStr, value: Option<&OsStr>) {
+    match value {
+        Some(value) => std::env::set_var(key, value),
+        None => std::env::remove_var(key),
+    }
+}
+
+/// Restores saved variables in reverse order, so a key set twice ends up with its original value
+struct RestoreVars(Vec<(OsString, Option<OsString>)>);
+
+impl Drop for RestoreVars {
+    fn drop(&mut self) {
+        for (key, value) in self.0.drain(..).rev() {
+            set_or_remove(&key, value.as_deref());
+        }
+    }
 }
 
 #[cfg(test)]
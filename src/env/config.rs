@@ -0,0 +1,257 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+
+/// Build a `T` from environment variables, in the style of the `envy` crate
+///
+/// Each field of the struct `T` is read from the environment variable with the upper-cased field
+/// name, so `port: u16` reads `PORT`. [Option] fields may be missing, sequences are
+/// comma-separated, and enums are matched by unit variant name. Errors name the variable and, for
+/// unparseable values, the expected type:
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     anyhow_std_doctest_port: u16,
+/// }
+///
+/// std::env::set_var("ANYHOW_STD_DOCTEST_PORT", "http");
+/// let res = anyhow_std::env::from_env_anyhow::<Config>();
+/// assert_eq!(
+///     format!("{:#}", res.err().unwrap()),
+///     r#"environment variable "ANYHOW_STD_DOCTEST_PORT": invalid value "http", expected u16: invalid digit found in string"#,
+/// );
+/// ```
+pub fn from_env_anyhow<T>() -> anyhow::Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_vars(std::env::vars_os())
+}
+
+/// Build a `T` as [from_env_anyhow] does, from `vars` rather than the process environment
+pub(crate) fn from_vars<T, I>(vars: I) -> anyhow::Result<T>
+where
+    T: DeserializeOwned,
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    let vars: HashMap<OsString, OsString> = vars.into_iter().collect();
+    Ok(T::deserialize(Env(&vars))?)
+}
+
+fn var_name(field: &str) -> String {
+    field.to_uppercase()
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error(format!(
+            "environment variable {:?}: environment variable not found",
+            var_name(field)
+        ))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The whole environment, which only deserializes as a struct
+struct Env<'a>(&'a HashMap<OsString, OsString>);
+
+impl<'de, 'a> de::Deserializer<'de> for Env<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error(
+            "only structs can be deserialized from the environment".to_string(),
+        ))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(Fields {
+            vars: self.0,
+            fields: fields.iter(),
+            value: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// The variables of the struct fields which are present in the environment
+struct Fields<'a> {
+    vars: &'a HashMap<OsString, OsString>,
+    fields: std::slice::Iter<'static, &'static str>,
+    value: Option<(String, &'a OsString)>,
+}
+
+impl<'de, 'a> MapAccess<'de> for Fields<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        for &field in self.fields.by_ref() {
+            let name = var_name(field);
+            if let Some(value) = self.vars.get(OsStr::new(&name)) {
+                self.value = Some((name, value));
+                return seed
+                    .deserialize(IntoDeserializer::<Error>::into_deserializer(field))
+                    .map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (name, value) = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        match value.to_str() {
+            Some(s) => seed.deserialize(Value(s)),
+            None => Err(Error(format!(
+                "invalid unicode {:?}",
+                value.to_string_lossy()
+            ))),
+        }
+        .map_err(|e| Error(format!("environment variable {:?}: {}", name, e)))
+    }
+}
+
+/// A single variable's value
+struct Value<'a>(&'a str);
+
+macro_rules! parse_value {
+    ( $( $method:ident => $visit:ident $ty:ty ),* ) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                let v = self.0.parse::<$ty>().map_err(|e| {
+                    Error(format!(
+                        "invalid value {:?}, expected {}: {}",
+                        self.0,
+                        stringify!($ty),
+                        e
+                    ))
+                })?;
+                visitor.$visit(v)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Value<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.0)
+    }
+
+    parse_value!(
+        deserialize_bool => visit_bool bool,
+        deserialize_i8 => visit_i8 i8,
+        deserialize_i16 => visit_i16 i16,
+        deserialize_i32 => visit_i32 i32,
+        deserialize_i64 => visit_i64 i64,
+        deserialize_i128 => visit_i128 i128,
+        deserialize_u8 => visit_u8 u8,
+        deserialize_u16 => visit_u16 u16,
+        deserialize_u32 => visit_u32 u32,
+        deserialize_u64 => visit_u64 u64,
+        deserialize_u128 => visit_u128 u128,
+        deserialize_f32 => visit_f32 f32,
+        deserialize_f64 => visit_f64 f64,
+        deserialize_char => visit_char char
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let items = self.0.split(',').filter(|s| !s.is_empty()).map(Value);
+        visitor.visit_seq(de::value::SeqDeserializer::new(items))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.0))
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for Value<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
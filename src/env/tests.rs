@@ -26,3 +26,112 @@ fn var(key: &str) -> Result<String, String> {
 fn var_os(key: &str) -> Result<OsString, String> {
     stringify_error(env::var_os(key))
 }
+
+#[cfg(feature = "serde")]
+mod config {
+    use crate::env::config::from_vars;
+    use crate::testutils::{err_str, stringify_error};
+    use std::ffi::OsString;
+    use test_case::test_case;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        port: u16,
+        verbose: bool,
+        level: Option<Level>,
+        #[serde(default)]
+        hosts: Vec<String>,
+    }
+
+    #[test_case(
+        &[("PORT", "80"), ("VERBOSE", "true"), ("LEVEL", "info"), ("HOSTS", "a,b")]
+        => Ok(Config {
+            port: 80,
+            verbose: true,
+            level: Some(Level::Info),
+            hosts: vec!["a".to_string(), "b".to_string()],
+        })
+        ; "all"
+    )]
+    #[test_case(
+        &[("PORT", "80"), ("VERBOSE", "false"), ("UNRELATED", "x")]
+        => Ok(Config { port: 80, verbose: false, level: None, hosts: vec![] })
+        ; "optional missing"
+    )]
+    #[test_case(
+        &[("VERBOSE", "true")]
+        => err_str(r#"environment variable "PORT": environment variable not found"#)
+        ; "missing"
+    )]
+    #[test_case(
+        &[("PORT", "99999"), ("VERBOSE", "true")]
+        => err_str(r#"environment variable "PORT": invalid value "99999", expected u16: number too large to fit in target type"#)
+        ; "out of range"
+    )]
+    #[test_case(
+        &[("PORT", "80"), ("VERBOSE", "yes")]
+        => err_str(r#"environment variable "VERBOSE": invalid value "yes", expected bool: provided string was not `true` or `false`"#)
+        ; "invalid bool"
+    )]
+    #[test_case(
+        &[("PORT", "80"), ("VERBOSE", "true"), ("LEVEL", "loud")]
+        => err_str(r#"environment variable "LEVEL": unknown variant `loud`, expected `debug` or `info`"#)
+        ; "invalid variant"
+    )]
+    fn from_env(vars: &[(&str, &str)]) -> Result<Config, String> {
+        stringify_error(from_vars(
+            vars.iter()
+                .map(|(k, v)| (OsString::from(k), OsString::from(v))),
+        ))
+    }
+}
+
+#[test]
+fn with_vars_restores() -> anyhow::Result<()> {
+    const SET: &str = "ANYHOW_STD_TEST_WITH_VARS_SET";
+    const UNSET: &str = "ANYHOW_STD_TEST_WITH_VARS_UNSET";
+    std::env::set_var(UNSET, "original");
+
+    let res = env::with_vars_anyhow([(SET, Some("a")), (UNSET, None), (SET, Some("b"))], || {
+        assert_eq!(env::var(SET)?, "b");
+        assert!(env::var(UNSET).is_err());
+        Err::<(), _>(anyhow::anyhow!("failed"))
+    });
+    assert_eq!(res.err().unwrap().to_string(), "failed");
+    assert!(env::var(SET).is_err());
+    assert_eq!(env::var(UNSET)?, "original");
+
+    let panicked = std::panic::catch_unwind(|| {
+        env::with_vars_anyhow([(UNSET, Some("changed"))], || -> anyhow::Result<()> {
+            panic!("in closure")
+        })
+    });
+    assert!(panicked.is_err());
+    assert_eq!(env::var(UNSET)?, "original");
+    Ok(())
+}
+
+#[test_case(
+    "BAD = SIGN", "x"
+    => err_str(r#"while setting environment variable "BAD = SIGN": environment variable contains '='"#)
+)]
+#[test_case(
+    "", "x"
+    => err_str(r#"while setting environment variable "": environment variable is empty"#)
+)]
+#[test_case(
+    "ANYHOW_STD_TEST_WITH_VARS_NUL", "a\0b"
+    => err_str(r#"while setting environment variable "ANYHOW_STD_TEST_WITH_VARS_NUL": in value: environment variable contains '\0'"#)
+)]
+fn with_vars_invalid(key: &str, value: &str) -> Result<(), String> {
+    stringify_error(env::with_vars_anyhow([(key, Some(value))], || {
+        panic!("closure should not be called")
+    }))
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+
+/// Aggregate multiple errors into a single [anyhow::Error]
+///
+/// Helpers which process many entries, such as
+/// [PathAnyhow::rename_matching_anyhow](crate::PathAnyhow::rename_matching_anyhow), keep going
+/// after a per-entry failure and report every failure at the end by returning an
+/// `ErrorCollection` as the error. Callers can recover it with [anyhow::Error::downcast_ref] to
+/// inspect individual errors.
+#[derive(Debug, Default)]
+pub struct ErrorCollection {
+    errors: Vec<anyhow::Error>,
+}
+
+impl ErrorCollection {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an error to the collection
+    pub fn push<E>(&mut self, error: E)
+    where
+        E: Into<anyhow::Error>,
+    {
+        self.errors.push(error.into());
+    }
+
+    /// Convert `res` into an [Option], adding any error to the collection
+    pub fn ok<T>(&mut self, res: anyhow::Result<T>) -> Option<T> {
+        res.map_err(|e| self.push(e)).ok()
+    }
+
+    /// Return `true` if no errors have been collected
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Return the number of collected errors
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Iterate over the collected errors
+    pub fn iter(&self) -> std::slice::Iter<'_, anyhow::Error> {
+        self.errors.iter()
+    }
+
+    /// Produce `Ok(value)` if the collection is empty, otherwise an error containing the collection
+    pub fn into_result<T>(self, value: T) -> anyhow::Result<T> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(anyhow::Error::new(self))
+        }
+    }
+}
+
+/// Choose whether a helper processing many entries stops at the first error
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Return the first error immediately
+    FailFast,
+    /// Process every entry, then return all errors as an [ErrorCollection]
+    #[default]
+    Collect,
+}
+
+impl ErrorPolicy {
+    /// Handle `res` according to this policy
+    ///
+    /// `Collect` adds an error to `errors` and returns `Ok(None)`, whereas `FailFast` returns it.
+    pub fn handle<T>(
+        self,
+        errors: &mut ErrorCollection,
+        res: anyhow::Result<T>,
+    ) -> anyhow::Result<Option<T>> {
+        match (self, res) {
+            (_, Ok(x)) => Ok(Some(x)),
+            (ErrorPolicy::FailFast, Err(e)) => Err(e),
+            (ErrorPolicy::Collect, Err(e)) => {
+                errors.push(e);
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl IntoIterator for ErrorCollection {
+    type Item = anyhow::Error;
+    type IntoIter = std::vec::IntoIter<anyhow::Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ErrorCollection {
+    type Item = &'a anyhow::Error;
+    type IntoIter = std::slice::Iter<'a, anyhow::Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<anyhow::Error> for ErrorCollection {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = anyhow::Error>,
+    {
+        ErrorCollection {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<anyhow::Error> for ErrorCollection {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = anyhow::Error>,
+    {
+        self.errors.extend(iter)
+    }
+}
+
+impl fmt::Display for ErrorCollection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = self.len();
+        write!(f, "{} error{}:", n, if n == 1 { "" } else { "s" })?;
+        for e in self {
+            write!(f, "\n- {:#}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ErrorCollection {}
+
+#[cfg(test)]
+mod tests;
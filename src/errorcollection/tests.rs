@@ -0,0 +1,56 @@
+use crate::ErrorCollection;
+use anyhow::anyhow;
+
+#[test]
+fn empty_into_result_ok() -> anyhow::Result<()> {
+    let errors = ErrorCollection::new();
+    assert!(errors.is_empty());
+    assert_eq!(errors.into_result(42)?, 42);
+    Ok(())
+}
+
+#[test]
+fn display_and_downcast() {
+    let mut errors = ErrorCollection::new();
+    assert_eq!(errors.ok(Ok::<_, anyhow::Error>("kept")), Some("kept"));
+    assert_eq!(
+        errors.ok::<()>(Err(anyhow!("first").context("while doing a"))),
+        None
+    );
+    errors.push(anyhow!("second"));
+    assert_eq!(errors.len(), 2);
+
+    let err = errors.into_result(()).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        "2 errors:\n- while doing a: first\n- second"
+    );
+    assert_eq!(err.downcast_ref::<ErrorCollection>().unwrap().len(), 2);
+}
+
+#[test]
+fn display_single() {
+    let errors: ErrorCollection = std::iter::once(anyhow!("only")).collect();
+    assert_eq!(errors.to_string(), "1 error:\n- only");
+}
+
+#[test]
+fn policy_handle() {
+    use crate::ErrorPolicy;
+
+    let mut errors = ErrorCollection::new();
+    assert_eq!(
+        ErrorPolicy::Collect
+            .handle::<()>(&mut errors, Err(anyhow!("kept")))
+            .unwrap(),
+        None
+    );
+    assert_eq!(errors.len(), 1);
+
+    let err = ErrorPolicy::FailFast
+        .handle::<()>(&mut errors, Err(anyhow!("returned")))
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "returned");
+    assert_eq!(errors.len(), 1);
+}
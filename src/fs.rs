@@ -1,9 +1,69 @@
 //! Wrappers for [std::fs] which provide paths in error contexts
+//!
+//! Besides the wrapper types, this module provides free functions mirroring those of [std::fs],
+//! for callers who prefer them over [PathAnyhow](crate::PathAnyhow) methods. Each delegates to the
+//! corresponding `…_anyhow` method, so error contexts are identical:
+//!
+//! ```
+//! let res = anyhow_std::fs::read("/this/path/should/not/exist");
+//! assert_eq!(
+//!     format!("{:#}", res.err().unwrap()),
+//!     r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+//! );
+//! ```
 
+mod chunks;
+#[cfg(feature = "sniff")]
+mod contentkind;
+mod copyoptions;
+mod currentdirguard;
+#[cfg(feature = "sha2")]
+mod digest;
+mod dirbuilder;
 mod direntry;
+mod entrypath;
+mod extractreport;
+mod filekind;
+mod followsymlinks;
+mod functions;
+mod handles;
+mod lineending;
+mod manifest;
 mod metadata;
+mod openoptions;
 mod readdir;
+mod sanitize;
+mod walk;
+mod writemode;
 
-pub use self::direntry::DirEntry;
+pub use self::chunks::Chunks;
+#[cfg(feature = "sniff")]
+pub use self::contentkind::ContentKind;
+#[cfg(feature = "sniff")]
+pub(crate) use self::contentkind::SNIFF_LEN;
+pub use self::copyoptions::{CopyOptions, SymlinkPolicy};
+pub use self::currentdirguard::CurrentDirGuard;
+#[cfg(feature = "sha2")]
+pub(crate) use self::digest::sha256_hex;
+#[allow(deprecated)]
+pub use self::dirbuilder::{DirBuilder, DirBuilderAnyhow};
+pub use self::direntry::{DirEntry, DirEntryAnyhow};
+pub use self::entrypath::resolve_entry_path_anyhow;
+pub use self::extractreport::ExtractReport;
+pub use self::filekind::FileKind;
+pub use self::followsymlinks::FollowSymlinks;
+pub use self::functions::{
+    canonicalize, copy, create_dir, create_dir_all, hard_link, metadata, read, read_dir, read_link,
+    read_to_string, remove_dir, remove_dir_all, remove_file, rename, set_permissions,
+    symlink_metadata, write,
+};
+pub use self::handles::{open_handle_limit, set_open_handle_limit, DEFAULT_OPEN_HANDLE_LIMIT};
+pub(crate) use self::lineending::normalize_to_lf;
+pub use self::lineending::LineEnding;
+pub use self::manifest::{Manifest, ManifestDiff, ManifestEntry};
 pub use self::metadata::Metadata;
+pub use self::openoptions::{OpenOptionsAnyhow, OpenOptionsBuilder};
 pub use self::readdir::ReadDir;
+pub use self::sanitize::{sanitize_file_name_anyhow, NameChange};
+pub use self::walk::Walk;
+pub use self::writemode::WriteMode;
@@ -0,0 +1,82 @@
+use crate::context::{value_context, with_path_context};
+use anyhow::Context;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+
+/// An iterator over fixed-size chunks of a file, returned by
+/// [PathAnyhow::chunks_anyhow](crate::PathAnyhow::chunks_anyhow)
+///
+/// Every chunk holds `chunk_size` bytes except possibly the last. Errors include the path and
+/// the byte offset of the failed read, after which the iterator ends.
+#[derive(Debug)]
+pub struct Chunks {
+    file: Option<File>,
+    path: PathBuf,
+    chunk_size: usize,
+    offset: u64,
+}
+
+impl Chunks {
+    pub(crate) fn open(path: &Path, chunk_size: usize) -> anyhow::Result<Self> {
+        if chunk_size == 0 {
+            return with_path_context(Err(anyhow::anyhow!("chunk size must not be zero")), path);
+        }
+        let file = with_path_context(File::open(path), path)?;
+        Ok(Chunks {
+            file: Some(file),
+            path: path.to_path_buf(),
+            chunk_size,
+            offset: 0,
+        })
+    }
+
+    /// The offset of the next chunk from the start of the file
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    fn read_chunk(file: &mut File, chunk_size: usize) -> std::io::Result<Vec<u8>> {
+        let mut chunk = vec![0; chunk_size];
+        let mut filled = 0;
+        while filled < chunk_size {
+            match file.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        chunk.truncate(filled);
+        Ok(chunk)
+    }
+}
+
+impl Iterator for Chunks {
+    type Item = anyhow::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let file = self.file.as_mut()?;
+        match Self::read_chunk(file, self.chunk_size) {
+            Ok(chunk) if chunk.is_empty() => {
+                self.file = None;
+                None
+            }
+            Ok(chunk) => {
+                self.offset += chunk.len() as u64;
+                Some(Ok(chunk))
+            }
+            Err(e) => {
+                self.file = None;
+                let offset = self.offset;
+                Some(with_path_context(
+                    Err(e).with_context(|| value_context("offset", offset)),
+                    &self.path,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,37 @@
+use crate::PathAnyhow;
+use test_case::test_case;
+
+#[test_case(b"", 3 => Vec::<Vec<u8>>::new(); "empty")]
+#[test_case(b"abcdef", 3 => vec![b"abc".to_vec(), b"def".to_vec()]; "exact")]
+#[test_case(b"abcdefg", 3 => vec![b"abc".to_vec(), b"def".to_vec(), b"g".to_vec()]; "remainder")]
+fn chunks(contents: &[u8], chunk_size: usize) -> Vec<Vec<u8>> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    path.write_anyhow(contents).unwrap();
+    path.chunks_anyhow(chunk_size)
+        .unwrap()
+        .collect::<anyhow::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn read_error_offset() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let mut chunks = dir.path().chunks_anyhow(4)?;
+    let err = chunks.next().unwrap().err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", dir.path().display()), "DIR"),
+        "while processing path DIR: with offset 0: Is a directory (os error 21)"
+    );
+    assert!(chunks.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn zero_chunk_size() {
+    let err = std::path::Path::new("file").chunks_anyhow(0).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        r#"while processing path "file": chunk size must not be zero"#
+    );
+}
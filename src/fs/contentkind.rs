@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// The format of a file as recognized from its first bytes by
+/// [PathAnyhow::detect_kind_anyhow](crate::PathAnyhow::detect_kind_anyhow)
+///
+/// This module requires the `sniff` feature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentKind {
+    /// gzip compressed data
+    Gzip,
+    /// A zip archive, including formats built on it such as jar files
+    Zip,
+    /// A POSIX tar archive
+    Tar,
+    /// An ELF executable or library
+    Elf,
+    /// A PE executable or library, as used on Windows
+    Pe,
+    /// A PNG image
+    Png,
+    /// A JPEG image
+    Jpeg,
+    /// A PDF document
+    Pdf,
+    /// UTF-16 text with a little-endian byte order mark
+    Utf16Le,
+    /// UTF-16 text with a big-endian byte order mark
+    Utf16Be,
+    /// None of the above
+    Unknown,
+}
+
+/// The number of leading bytes needed to recognize every [ContentKind]
+pub(crate) const SNIFF_LEN: usize = 512;
+
+impl ContentKind {
+    /// Recognize the kind of content starting with `prefix`, which should hold the first
+    /// 512 bytes of the file or all of it if shorter
+    pub fn sniff(prefix: &[u8]) -> Self {
+        const MAGIC: &[(&[u8], ContentKind)] = &[
+            (b"\x1f\x8b", ContentKind::Gzip),
+            (b"PK\x03\x04", ContentKind::Zip),
+            (b"PK\x05\x06", ContentKind::Zip),
+            (b"\x7fELF", ContentKind::Elf),
+            (b"MZ", ContentKind::Pe),
+            (b"\x89PNG\r\n\x1a\n", ContentKind::Png),
+            (b"\xff\xd8\xff", ContentKind::Jpeg),
+            (b"%PDF-", ContentKind::Pdf),
+            (b"\xff\xfe", ContentKind::Utf16Le),
+            (b"\xfe\xff", ContentKind::Utf16Be),
+        ];
+
+        if let Some(&(_, kind)) = MAGIC.iter().find(|(magic, _)| prefix.starts_with(magic)) {
+            kind
+        } else if prefix.get(257..262) == Some(b"ustar") {
+            ContentKind::Tar
+        } else {
+            ContentKind::Unknown
+        }
+    }
+}
+
+impl fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ContentKind::Gzip => "gzip data",
+            ContentKind::Zip => "a zip archive",
+            ContentKind::Tar => "a tar archive",
+            ContentKind::Elf => "an ELF executable",
+            ContentKind::Pe => "a PE executable",
+            ContentKind::Png => "a PNG image",
+            ContentKind::Jpeg => "a JPEG image",
+            ContentKind::Pdf => "a PDF document",
+            ContentKind::Utf16Le => "UTF-16LE text",
+            ContentKind::Utf16Be => "UTF-16BE text",
+            ContentKind::Unknown => "unrecognized content",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
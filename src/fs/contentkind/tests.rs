@@ -0,0 +1,39 @@
+use crate::fs::ContentKind;
+use crate::PathAnyhow;
+use test_case::test_case;
+
+#[test_case(b"\x1f\x8b\x08\x00" => ContentKind::Gzip; "gzip")]
+#[test_case(b"PK\x03\x04rest" => ContentKind::Zip; "zip")]
+#[test_case(b"\x7fELF\x02\x01" => ContentKind::Elf; "elf")]
+#[test_case(b"MZ\x90\x00" => ContentKind::Pe; "pe")]
+#[test_case(b"\x89PNG\r\n\x1a\n" => ContentKind::Png; "png")]
+#[test_case(b"%PDF-1.7" => ContentKind::Pdf; "pdf")]
+#[test_case(b"\xff\xfeh\x00" => ContentKind::Utf16Le; "utf16le")]
+#[test_case(b"" => ContentKind::Unknown; "empty")]
+#[test_case(b"plain text" => ContentKind::Unknown; "text")]
+fn sniff(prefix: &[u8]) -> ContentKind {
+    ContentKind::sniff(prefix)
+}
+
+#[test]
+fn sniff_tar() {
+    let mut header = vec![0; 512];
+    header[257..262].copy_from_slice(b"ustar");
+    assert_eq!(ContentKind::sniff(&header), ContentKind::Tar);
+}
+
+#[test]
+fn detect_and_expect() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("archive.zip");
+    path.write_anyhow(b"\x1f\x8b\x08\x00payload")?;
+
+    assert_eq!(path.detect_kind_anyhow()?, ContentKind::Gzip);
+    path.expect_kind_anyhow(ContentKind::Gzip)?;
+    let err = path.expect_kind_anyhow(ContentKind::Zip).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", path.display()), "PATH"),
+        "while processing path PATH: expected a zip archive, but the contents look like gzip data"
+    );
+    Ok(())
+}
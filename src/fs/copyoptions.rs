@@ -0,0 +1,35 @@
+/// Options for [PathAnyhow::copy_dir_all_anyhow](crate::PathAnyhow::copy_dir_all_anyhow)
+///
+/// By default, existing destination files are an error and symbolic links are recreated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CopyOptions {
+    pub(crate) overwrite: bool,
+    pub(crate) symlinks: SymlinkPolicy,
+}
+
+/// How [PathAnyhow::copy_dir_all_anyhow](crate::PathAnyhow::copy_dir_all_anyhow) treats symbolic
+/// links in the source tree
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Create a link with the same target in the destination
+    #[default]
+    Recreate,
+    /// Copy what the link resolves to
+    Follow,
+    /// Leave links out of the copy
+    Skip,
+}
+
+impl CopyOptions {
+    /// Replace existing destination files and links instead of failing
+    pub fn overwrite(mut self, enabled: bool) -> Self {
+        self.overwrite = enabled;
+        self
+    }
+
+    /// Choose how symbolic links are copied
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+}
@@ -0,0 +1,77 @@
+use crate::context::change_dir_context;
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// Restores the previous current directory when dropped
+///
+/// Created by [PathAnyhow::push_dir_anyhow](crate::PathAnyhow::push_dir_anyhow). Dropping the
+/// guard restores the directory on a best-effort basis; call [CurrentDirGuard::restore_anyhow]
+/// to handle failures.
+#[derive(Debug)]
+#[must_use = "the previous current directory is restored as soon as the guard is dropped"]
+pub struct CurrentDirGuard {
+    previous: Option<PathBuf>,
+}
+
+impl CurrentDirGuard {
+    /// Change the current directory to `path`, optionally refusing when other threads exist
+    pub(crate) fn push(path: &Path, single_threaded_only: bool) -> anyhow::Result<Self> {
+        let previous = std::env::current_dir().context("while reading the current directory")?;
+        let res = if single_threaded_only {
+            check_single_threaded()
+        } else {
+            Ok(())
+        };
+        res.and_then(|()| std::env::set_current_dir(path).map_err(anyhow::Error::from))
+            .with_context(|| change_dir_context(&previous, path, false))?;
+        Ok(CurrentDirGuard {
+            previous: Some(previous),
+        })
+    }
+
+    /// The directory which is restored by this guard
+    pub fn previous(&self) -> &Path {
+        self.previous.as_deref().expect("guard already restored")
+    }
+
+    /// Restore the previous current directory, reporting failure
+    pub fn restore_anyhow(mut self) -> anyhow::Result<()> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> anyhow::Result<()> {
+        let Some(previous) = self.previous.take() else {
+            return Ok(());
+        };
+        let current = std::env::current_dir().unwrap_or_default();
+        std::env::set_current_dir(&previous)
+            .with_context(|| change_dir_context(&current, &previous, true))
+    }
+}
+
+impl Drop for CurrentDirGuard {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+/// The current directory is shared by every thread, so changing it while other threads run can
+/// silently redirect their relative paths.
+fn check_single_threaded() -> anyhow::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let threads = std::fs::read_dir("/proc/self/task")
+            .context("while counting threads")?
+            .count();
+        if threads > 1 {
+            anyhow::bail!(
+                "refusing to change the process-wide current directory while {} threads are running",
+                threads
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
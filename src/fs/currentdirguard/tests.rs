@@ -0,0 +1,76 @@
+use crate::fs::CurrentDirGuard;
+use crate::PathAnyhow;
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+#[test]
+fn refuses_with_threads() {
+    // The test harness runs each test on its own thread, so there are always several.
+    let err = Path::new("/").push_dir_anyhow().err().unwrap();
+    let msg = format!("{:#}", err);
+    assert!(
+        msg.starts_with("while changing current directory from "),
+        "{}",
+        msg
+    );
+    assert!(
+        msg.contains(r#" to "/": refusing to change the process-wide current directory while "#),
+        "{}",
+        msg
+    );
+}
+
+/// Set in the child process which runs [push_and_restore] alone
+const CHILD_VAR: &str = "ANYHOW_STD_TEST_CURRENT_DIR_CHILD";
+
+#[test]
+fn push_and_restore() -> anyhow::Result<()> {
+    use crate::process::CommandAnyhow;
+
+    if std::env::var_os(CHILD_VAR).is_some() {
+        return push_and_restore_in_child();
+    }
+    // The current directory is process-wide, so changing it here would redirect the relative
+    // paths of tests running concurrently; rerun just this test in a child process instead.
+    let module = module_path!().split_once("::").unwrap().1;
+    let output = std::process::Command::new(std::env::current_exe()?)
+        .args([
+            "--exact",
+            &format!("{module}::push_and_restore"),
+            "--nocapture",
+        ])
+        .env(CHILD_VAR, "1")
+        .output_anyhow()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+    Ok(())
+}
+
+fn push_and_restore_in_child() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let original = std::env::current_dir()?;
+
+    let missing = dir.path().join("missing");
+    let err = CurrentDirGuard::push(&missing, false).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        format!(
+            "while changing current directory from {:?} to {:?}: No such file or directory (os error 2)",
+            original.display(),
+            missing.display()
+        )
+    );
+
+    {
+        let guard = CurrentDirGuard::push(dir.path(), false)?;
+        assert_eq!(guard.previous(), original);
+        assert_eq!(std::env::current_dir()?, dir.path().canonicalize_anyhow()?);
+    }
+    assert_eq!(std::env::current_dir()?, original);
+
+    let guard = CurrentDirGuard::push(dir.path(), false)?;
+    guard.restore_anyhow()?;
+    assert_eq!(std::env::current_dir()?, original);
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use crate::context::with_path_context;
+use crate::PathAnyhow;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Compute the lowercase hex SHA-256 digest of the contents of `path`
+pub(crate) fn sha256_hex(path: &Path) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut file = path.open_file_anyhow()?;
+    with_path_context(std::io::copy(&mut file, &mut hasher), path)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
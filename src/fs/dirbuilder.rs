@@ -0,0 +1,103 @@
+use crate::audit::audited;
+use crate::context::{value_context, with_path_context};
+use anyhow::Context;
+use std::path::Path;
+
+/// Mirror [std::fs::DirBuilder], providing the path and options as error context
+///
+/// ```
+/// use anyhow_std::fs::DirBuilder;
+///
+/// let res = DirBuilder::new().recursive(true).create_anyhow("/proc/no/such/dir");
+/// assert_eq!(
+///     format!("{:#}", res.err().unwrap()),
+///     r#"while processing path "/proc/no/such/dir": with options recursive: No such file or directory (os error 2)"#,
+/// );
+/// ```
+#[derive(Debug)]
+pub struct DirBuilder {
+    inner: std::fs::DirBuilder,
+    recursive: bool,
+    #[cfg(target_family = "unix")]
+    mode: Option<u32>,
+}
+
+/// The former name of [DirBuilder]
+#[deprecated(note = "renamed to `DirBuilder`")]
+pub type DirBuilderAnyhow = DirBuilder;
+
+impl Default for DirBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirBuilder {
+    /// Create a builder with the [std::fs::DirBuilder::new] defaults
+    pub fn new() -> Self {
+        DirBuilder {
+            inner: std::fs::DirBuilder::new(),
+            recursive: false,
+            #[cfg(target_family = "unix")]
+            mode: None,
+        }
+    }
+
+    /// Wrap [std::fs::DirBuilder::recursive]
+    pub fn recursive(&mut self, recursive: bool) -> &mut Self {
+        self.inner.recursive(recursive);
+        self.recursive = recursive;
+        self
+    }
+
+    /// Wrap [DirBuilderExt::mode](std::os::unix::fs::DirBuilderExt::mode)
+    #[cfg(target_family = "unix")]
+    pub fn mode(&mut self, mode: u32) -> &mut Self {
+        use std::os::unix::fs::DirBuilderExt;
+
+        self.inner.mode(mode);
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Wrap [std::fs::DirBuilder::create], providing the path and options as error context
+    pub fn create_anyhow<P>(&self, path: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let operation = if self.recursive {
+            "create_dir_all"
+        } else {
+            "create_dir"
+        };
+        audited(operation, &[path], || {
+            with_path_context(
+                self.inner
+                    .create(path)
+                    .with_context(|| value_context("options", self.describe_options())),
+                path,
+            )
+        })
+    }
+
+    fn describe_options(&self) -> String {
+        let mut options = vec![];
+        if self.recursive {
+            options.push("recursive".to_string());
+        }
+        #[cfg(target_family = "unix")]
+        if let Some(mode) = self.mode {
+            options.push(format!("mode {:#o}", mode));
+        }
+
+        if options.is_empty() {
+            "none".to_string()
+        } else {
+            options.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,41 @@
+use crate::fs::DirBuilder;
+use crate::PathAnyhow;
+
+#[test]
+fn create() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let nested = dir.path().join("a/b");
+
+    let err = DirBuilder::new().create_anyhow(&nested).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", nested.display()), "PATH"),
+        "while processing path PATH: with options none: No such file or directory (os error 2)"
+    );
+
+    DirBuilder::new().recursive(true).create_anyhow(&nested)?;
+    assert!(nested.metadata_anyhow()?.is_dir());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn create_with_mode() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("private");
+    let mut builder = DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+
+    builder.create_anyhow(&path)?;
+    assert_eq!(path.metadata_anyhow()?.permissions().mode() & 0o777, 0o700);
+
+    let file = dir.path().join("file");
+    file.write_anyhow("")?;
+    let err = builder.create_anyhow(file.join("sub")).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR"),
+        r#"while processing path "DIR/file/sub": with options recursive, mode 0o700: Not a directory (os error 20)"#
+    );
+    Ok(())
+}
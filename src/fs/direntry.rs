@@ -1,8 +1,46 @@
+use crate::context::with_path_context;
 use crate::fs::Metadata;
-use anyhow::Context;
 use std::fs::FileType;
 use std::ops::Deref;
 
+/// Extension methods for [std::fs::DirEntry] providing the entry path as error context
+///
+/// [DirEntry] implements the same methods for entries yielded by
+/// [PathAnyhow::read_dir_anyhow](crate::PathAnyhow::read_dir_anyhow).
+pub trait DirEntryAnyhow {
+    /// Wrap [std::fs::DirEntry::metadata], providing the path as error context
+    fn metadata_anyhow(&self) -> anyhow::Result<Metadata>;
+
+    /// Wrap [std::fs::DirEntry::file_type], providing the path as error context
+    fn file_type_anyhow(&self) -> anyhow::Result<FileType>;
+
+    /// Return the file name, or an error with the path as context if it is not valid UTF-8
+    fn file_name_str_anyhow(&self) -> anyhow::Result<String>;
+}
+
+impl DirEntryAnyhow for std::fs::DirEntry {
+    fn metadata_anyhow(&self) -> anyhow::Result<Metadata> {
+        let path = self.path();
+        with_path_context(
+            self.metadata().map(|md| Metadata::from((md, path.clone()))),
+            &path,
+        )
+    }
+
+    fn file_type_anyhow(&self) -> anyhow::Result<FileType> {
+        with_path_context(self.file_type(), &self.path())
+    }
+
+    fn file_name_str_anyhow(&self) -> anyhow::Result<String> {
+        with_path_context(
+            self.file_name()
+                .into_string()
+                .map_err(|_| anyhow::anyhow!("invalid UTF8")),
+            &self.path(),
+        )
+    }
+}
+
 /// Wraps [std::fs::DirEntry] to provide the path as error context
 #[derive(Debug, derive_more::From, derive_more::Into)]
 pub struct DirEntry {
@@ -12,17 +50,17 @@ pub struct DirEntry {
 impl DirEntry {
     /// Extend [std::fs::DirEntry::metadata] providing the path in the error context
     pub fn metadata(&self) -> anyhow::Result<Metadata> {
-        self.de
-            .metadata()
-            .map(|md| Metadata::from((md, self.path())))
-            .with_context(|| format!("while processing path {:?}", self.path().display()))
+        self.de.metadata_anyhow()
     }
 
     /// Extend [std::fs::DirEntry::file_type] providing the path in the error context
     pub fn file_type(&self) -> anyhow::Result<FileType> {
-        self.de
-            .file_type()
-            .with_context(|| format!("while processing path {:?}", self.path().display()))
+        self.de.file_type_anyhow()
+    }
+
+    /// Return the file name, or an error with the path as context if it is not valid UTF-8
+    pub fn file_name_str(&self) -> anyhow::Result<String> {
+        self.de.file_name_str_anyhow()
     }
 }
 
@@ -33,3 +71,6 @@ impl Deref for DirEntry {
         &self.de
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,34 @@
+use crate::fs::DirEntryAnyhow;
+use crate::PathAnyhow;
+
+#[test]
+fn std_entry() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    dir.path().join("file").write_anyhow("abc")?;
+
+    let entry = std::fs::read_dir(dir.path())?.next().unwrap()?;
+    assert_eq!(entry.file_name_str_anyhow()?, "file");
+    assert!(entry.file_type_anyhow()?.is_file());
+    assert_eq!(entry.metadata_anyhow()?.len(), 3);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn invalid_utf8_name() -> anyhow::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join(std::ffi::OsStr::from_bytes(b"bad\xff"));
+    path.write_anyhow("")?;
+
+    let entry = dir.path().read_dir_anyhow()?.next().unwrap()?;
+    assert_eq!(
+        format!("{:#}", entry.file_name_str().err().unwrap()),
+        format!(
+            r#"while processing path {}: invalid UTF8"#,
+            crate::escape_os(&path)
+        )
+    );
+    Ok(())
+}
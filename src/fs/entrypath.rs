@@ -0,0 +1,58 @@
+use crate::context::{render_path, value_context, with_path_context};
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// Resolve the archive entry `entry_name` to the path it should be extracted to below `dest_root`,
+/// guarding against "zip slip"
+///
+/// Both `/` and `\` separate components, since archives created on Windows may use either. The
+/// entry is joined as [PathAnyhow::join_secure_anyhow] does, so absolute names, drive prefixes such
+/// as `C:`, and `..` components escaping `dest_root` are rejected. Additionally, no existing path
+/// between `dest_root` and the result, including the result itself, may be a symbolic link, since
+/// an earlier entry could have planted one pointing outside `dest_root`. Errors name `dest_root`
+/// and the entry.
+pub fn resolve_entry_path_anyhow<P>(dest_root: P, entry_name: &str) -> anyhow::Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let root = dest_root.as_ref();
+    with_path_context(
+        resolve(root, entry_name)
+            .with_context(|| value_context("archive entry", format_args!("{entry_name:?}"))),
+        root,
+    )
+}
+
+fn resolve(root: &Path, entry_name: &str) -> anyhow::Result<PathBuf> {
+    if entry_name.is_empty() {
+        anyhow::bail!("entry name is empty");
+    }
+    if entry_name.starts_with(['/', '\\']) {
+        anyhow::bail!("absolute entry names are not allowed");
+    }
+    let components: Vec<&str> = entry_name.split(['/', '\\']).collect();
+    if components[0].ends_with(':') {
+        anyhow::bail!("prefix component {:?} is not allowed", components[0]);
+    }
+
+    let path = crate::path::join_components(root, &components.iter().collect::<PathBuf>())?;
+    let mut ancestor = root.to_path_buf();
+    for component in path.strip_prefix_anyhow(root)? {
+        ancestor.push(component);
+        match std::fs::symlink_metadata(&ancestor) {
+            Ok(md) if md.file_type().is_symlink() => anyhow::bail!(
+                "entry passes through symbolic link {}",
+                render_path(&ancestor)
+            ),
+            Ok(_) => {}
+            // Nothing below a missing path can exist yet.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+            Err(e) => return with_path_context(Err(e), &ancestor),
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,46 @@
+use crate::fs::resolve_entry_path_anyhow;
+use crate::testutils::{err_str, stringify_error};
+use test_case::test_case;
+
+#[test_case("a/b.txt" => Ok("DIR/a/b.txt".to_string()); "nested")]
+#[test_case(r"a\b.txt" => Ok("DIR/a/b.txt".to_string()); "backslash")]
+#[test_case(
+    "../evil"
+    => err_str(r#"while processing path "DIR": with archive entry "../evil": component ".." escapes the base directory"#)
+    ; "parent"
+)]
+#[test_case(
+    "/etc/passwd"
+    => err_str(r#"while processing path "DIR": with archive entry "/etc/passwd": absolute entry names are not allowed"#)
+    ; "absolute"
+)]
+#[test_case(
+    r"C:\evil"
+    => err_str(r#"while processing path "DIR": with archive entry "C:\\evil": prefix component "C:" is not allowed"#)
+    ; "drive"
+)]
+fn resolve(entry_name: &str) -> Result<String, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let dir_str = dir.path().display().to_string();
+    stringify_error(
+        resolve_entry_path_anyhow(dir.path(), entry_name).map(|p| p.display().to_string()),
+    )
+    .map(|s| s.replace(&dir_str, "DIR"))
+    .map_err(|e| e.replace(&dir_str, "DIR"))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn resolve_through_symlink() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    std::os::unix::fs::symlink("/tmp", dir.path().join("link"))?;
+
+    let err = resolve_entry_path_anyhow(dir.path(), "link/evil")
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR"),
+        r#"while processing path "DIR": with archive entry "link/evil": entry passes through symbolic link "DIR/link""#
+    );
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+/// The outcome of [PathAnyhow::extract_assets_anyhow](crate::PathAnyhow::extract_assets_anyhow)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtractReport {
+    /// Assets which were missing or differed, and were written
+    pub written: Vec<PathBuf>,
+    /// Assets whose existing file already had the embedded contents
+    pub unchanged: Vec<PathBuf>,
+}
@@ -0,0 +1,85 @@
+use crate::context::with_path_context;
+use std::fmt;
+use std::fs::FileType;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// The kind of a filesystem entry, as returned by
+/// [PathAnyhow::classify_anyhow](crate::PathAnyhow::classify_anyhow)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    /// A regular file
+    File,
+    /// A directory
+    Dir,
+    /// A symbolic link with the kind of its fully resolved target, or `None` if it dangles
+    Symlink(Option<Box<FileKind>>),
+    /// A named pipe
+    Fifo,
+    /// A unix domain socket
+    Socket,
+    /// A block device
+    BlockDev,
+    /// A character device
+    CharDev,
+    /// Any other kind of entry
+    Unknown,
+}
+
+impl FileKind {
+    pub(crate) fn of_path(path: &Path) -> anyhow::Result<Self> {
+        let ft = with_path_context(path.symlink_metadata(), path)?.file_type();
+        if !ft.is_symlink() {
+            return Ok(FileKind::from_non_symlink(ft));
+        }
+
+        match path.metadata() {
+            Ok(md) => Ok(FileKind::Symlink(Some(Box::new(
+                FileKind::from_non_symlink(md.file_type()),
+            )))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(FileKind::Symlink(None)),
+            Err(e) => with_path_context(Err(e), path),
+        }
+    }
+
+    pub(crate) fn from_non_symlink(ft: FileType) -> Self {
+        if ft.is_file() {
+            return FileKind::File;
+        } else if ft.is_dir() {
+            return FileKind::Dir;
+        }
+
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::FileTypeExt;
+
+            if ft.is_fifo() {
+                return FileKind::Fifo;
+            } else if ft.is_socket() {
+                return FileKind::Socket;
+            } else if ft.is_block_device() {
+                return FileKind::BlockDev;
+            } else if ft.is_char_device() {
+                return FileKind::CharDev;
+            }
+        }
+
+        FileKind::Unknown
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileKind::File => f.write_str("regular file"),
+            FileKind::Dir => f.write_str("directory"),
+            FileKind::Symlink(Some(target)) => write!(f, "symbolic link to {}", target),
+            FileKind::Symlink(None) => f.write_str("dangling symbolic link"),
+            FileKind::Fifo => f.write_str("named pipe"),
+            FileKind::Socket => f.write_str("socket"),
+            FileKind::BlockDev => f.write_str("block device"),
+            FileKind::CharDev => f.write_str("character device"),
+            FileKind::Unknown => f.write_str("unknown entry"),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::fmt;
+
+/// Whether [PathAnyhow::stat_anyhow](crate::PathAnyhow::stat_anyhow) follows a final symbolic
+/// link
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FollowSymlinks {
+    /// Describe the link's target, like [std::fs::metadata]
+    Yes,
+    /// Describe the link itself, like [std::fs::symlink_metadata]
+    No,
+}
+
+impl fmt::Display for FollowSymlinks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FollowSymlinks::Yes => "yes",
+            FollowSymlinks::No => "no",
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use crate::fs::{Metadata, ReadDir};
+use crate::PathAnyhow;
+use std::fs::Permissions;
+use std::path::{Path, PathBuf};
+
+macro_rules! wrap_fn {
+    ( $name:ident, $method:ident, $ret:ty ) => {
+        #[doc = concat!(
+            "Wrap [std::fs::", stringify!($name), "] via [PathAnyhow::", stringify!($method), "]"
+        )]
+        pub fn $name<P>(path: P) -> anyhow::Result<$ret>
+        where
+            P: AsRef<Path>,
+        {
+            path.as_ref().$method()
+        }
+    };
+
+    ( $name:ident, $method:ident, $arg:ident, $ret:ty ) => {
+        #[doc = concat!(
+            "Wrap [std::fs::", stringify!($name), "] via [PathAnyhow::", stringify!($method), "]"
+        )]
+        pub fn $name<P, Q>(path: P, $arg: Q) -> anyhow::Result<$ret>
+        where
+            P: AsRef<Path>,
+            Q: AsRef<Path>,
+        {
+            path.as_ref().$method($arg)
+        }
+    };
+}
+
+wrap_fn!(canonicalize, canonicalize_anyhow, PathBuf);
+wrap_fn!(copy, copy_anyhow, to, u64);
+wrap_fn!(create_dir, create_dir_anyhow, ());
+wrap_fn!(create_dir_all, create_dir_all_anyhow, ());
+wrap_fn!(hard_link, hard_link_anyhow, link, ());
+wrap_fn!(metadata, metadata_anyhow, Metadata);
+wrap_fn!(read, read_anyhow, Vec<u8>);
+wrap_fn!(read_dir, read_dir_anyhow, ReadDir);
+wrap_fn!(read_link, read_link_anyhow, PathBuf);
+wrap_fn!(read_to_string, read_to_string_anyhow, String);
+wrap_fn!(remove_dir, remove_dir_anyhow, ());
+wrap_fn!(remove_dir_all, remove_dir_all_anyhow, ());
+wrap_fn!(remove_file, remove_file_anyhow, ());
+wrap_fn!(rename, rename_anyhow, to, ());
+wrap_fn!(symlink_metadata, symlink_metadata_anyhow, Metadata);
+
+/// Wrap [std::fs::set_permissions] via [PathAnyhow::set_permissions_anyhow]
+pub fn set_permissions<P>(path: P, perm: Permissions) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+{
+    path.as_ref().set_permissions_anyhow(perm)
+}
+
+/// Wrap [std::fs::write] via [PathAnyhow::write_anyhow]
+pub fn write<P, C>(path: P, contents: C) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+{
+    path.as_ref().write_anyhow(contents)
+}
+
+#[cfg(test)]
+mod tests;
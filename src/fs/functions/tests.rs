@@ -0,0 +1,52 @@
+use crate::fs;
+use crate::testutils::{err_str, stringify_error};
+use test_case::test_case;
+
+#[test_case(
+    "/this/path/should/not/exist"
+    => err_str(
+        r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+    )
+    ; "missing"
+)]
+fn read_to_string(input: &str) -> Result<String, String> {
+    stringify_error(fs::read_to_string(input))
+}
+
+#[cfg_attr(
+    not(feature = "compat-messages"),
+    test_case(
+        "/this/path/should/not/exist",
+        "/this/path/also/should/not/exist"
+        => err_str(
+            r#"while renaming path "/this/path/should/not/exist" to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#,
+        )
+        ; "missing"
+    )
+)]
+#[cfg_attr(
+    feature = "compat-messages",
+    test_case(
+        "/this/path/should/not/exist",
+        "/this/path/also/should/not/exist"
+        => err_str(
+            r#"while processing path "/this/path/should/not/exist": with rename_to "/this/path/also/should/not/exist": No such file or directory (os error 2)"#,
+        )
+        ; "missing"
+    )
+)]
+fn rename(from: &str, to: &str) -> Result<(), String> {
+    stringify_error(fs::rename(from, to))
+}
+
+#[test]
+fn write_read_remove() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file");
+    fs::write(&path, "hello")?;
+    assert_eq!(fs::read(&path)?, b"hello");
+    assert_eq!(fs::metadata(&path)?.len(), 5);
+    fs::remove_file(&path)?;
+    assert!(fs::symlink_metadata(&path).is_err());
+    Ok(())
+}
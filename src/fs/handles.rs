@@ -0,0 +1,68 @@
+use crate::context::{value_context, with_path_context};
+use crate::fs::ReadDir;
+use anyhow::Context;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static OPEN_HANDLE_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_OPEN_HANDLE_LIMIT);
+
+/// The default of [open_handle_limit]
+pub const DEFAULT_OPEN_HANDLE_LIMIT: usize = 64;
+
+/// Limit the directory handles each bulk helper keeps open at once
+///
+/// Helpers traversing a tree, such as
+/// [PathAnyhow::largest_entries_anyhow](crate::PathAnyhow::largest_entries_anyhow), keep one
+/// handle open per directory level. Beyond the limit, the remaining entries of a directory are
+/// read into memory and its handle closed, so deep trees do not exhaust the process's file
+/// descriptors. The setting is process-wide; a limit of zero is treated as one.
+pub fn set_open_handle_limit(limit: usize) {
+    OPEN_HANDLE_LIMIT.store(limit.max(1), Ordering::Relaxed);
+}
+
+/// The limit set by [set_open_handle_limit]
+pub fn open_handle_limit() -> usize {
+    OPEN_HANDLE_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Open `path` like [PathAnyhow::read_dir_anyhow](crate::PathAnyhow::read_dir_anyhow), adding
+/// the process limit and a suggestion if file descriptors are exhausted
+pub(crate) fn open_dir(path: &Path) -> anyhow::Result<ReadDir> {
+    with_path_context(annotate_exhaustion(ReadDir::from_path(path)), path)
+}
+
+pub(crate) fn annotate_exhaustion<T>(res: std::io::Result<T>) -> anyhow::Result<T> {
+    match res {
+        Err(e) if is_exhaustion(&e) => Err(e).with_context(|| {
+            let limit = match fd_soft_limit() {
+                Some(n) => n.to_string(),
+                None => "unknown".to_string(),
+            };
+            value_context(
+                "open file limit",
+                format_args!(
+                    "{}; raise it with \"ulimit -n\" or lower anyhow_std::fs::set_open_handle_limit",
+                    limit
+                ),
+            )
+        }),
+        res => Ok(res?),
+    }
+}
+
+fn is_exhaustion(e: &std::io::Error) -> bool {
+    // EMFILE and ENFILE share these values on Linux and the BSDs.
+    cfg!(target_family = "unix") && matches!(e.raw_os_error(), Some(23 | 24))
+}
+
+/// The soft limit on open file descriptors, where it can be read without `libc`
+fn fd_soft_limit() -> Option<u64> {
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    limits
+        .lines()
+        .find_map(|l| l.strip_prefix("Max open files"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,38 @@
+/// A line ending convention for
+/// [PathAnyhow::write_text_anyhow](crate::PathAnyhow::write_text_anyhow)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// The platform convention: [CrLf](LineEnding::CrLf) on Windows, otherwise [Lf](LineEnding::Lf)
+    #[default]
+    Native,
+}
+
+impl LineEnding {
+    /// The line terminator as a string
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+
+    /// Convert every `\r\n` or `\n` line ending in `text` to this convention
+    pub fn apply(self, text: &str) -> String {
+        let lf = normalize_to_lf(text);
+        match self.as_str() {
+            "\n" => lf,
+            eol => lf.replace('\n', eol),
+        }
+    }
+}
+
+/// Convert `\r\n` line endings to `\n`
+pub(crate) fn normalize_to_lf(text: &str) -> String {
+    text.replace("\r\n", "\n")
+}
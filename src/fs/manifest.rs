@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+/// A deterministic listing of the regular files below a directory, produced by
+/// [PathAnyhow::manifest_anyhow](crate::PathAnyhow::manifest_anyhow)
+///
+/// With the `serde` feature, manifests implement `serde::Serialize` and `serde::Deserialize`, so
+/// they can be written out and signed, for example by release tooling, then loaded again for
+/// [PathAnyhow::verify_manifest_anyhow](crate::PathAnyhow::verify_manifest_anyhow).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// One entry per regular file, sorted by path
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A regular file in a [Manifest]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    /// The path relative to the manifest root
+    pub path: PathBuf,
+    /// The size in bytes
+    pub size: u64,
+    /// The lowercase hex SHA-256 digest of the contents, if requested
+    pub sha256: Option<String>,
+}
+
+/// The differences found by
+/// [PathAnyhow::verify_manifest_anyhow](crate::PathAnyhow::verify_manifest_anyhow), each sorted
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    /// Files in the manifest which are absent from the tree
+    pub missing: Vec<PathBuf>,
+    /// Files in the tree which are absent from the manifest
+    pub extra: Vec<PathBuf>,
+    /// Files whose size or digest differs from the manifest
+    pub modified: Vec<PathBuf>,
+}
+
+impl ManifestDiff {
+    /// Return `true` if the tree matches the manifest
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests;
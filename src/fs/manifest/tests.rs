@@ -0,0 +1,225 @@
+use crate::fs::{Manifest, ManifestEntry};
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::de::{Deserialize, Deserializer, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize, Serializer};
+
+/// A minimal in-memory data model, as no serialization format is a dependency
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    U64(u64),
+    Str(String),
+    None,
+    Some(Box<Value>),
+    Seq(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+#[test]
+fn serde_round_trip() -> anyhow::Result<()> {
+    let manifest = Manifest {
+        entries: vec![
+            ManifestEntry {
+                path: "a.txt".into(),
+                size: 5,
+                sha256: Some("2cf24dba".into()),
+            },
+            ManifestEntry {
+                path: "sub/c".into(),
+                size: 0,
+                sha256: None,
+            },
+        ],
+    };
+
+    let value = manifest.serialize(ValueSerializer)?;
+    assert_eq!(
+        value,
+        Value::Map(vec![(
+            "entries".into(),
+            Value::Seq(vec![
+                Value::Map(vec![
+                    ("path".into(), Value::Str("a.txt".into())),
+                    ("size".into(), Value::U64(5)),
+                    (
+                        "sha256".into(),
+                        Value::Some(Box::new(Value::Str("2cf24dba".into())))
+                    ),
+                ]),
+                Value::Map(vec![
+                    ("path".into(), Value::Str("sub/c".into())),
+                    ("size".into(), Value::U64(0)),
+                    ("sha256".into(), Value::None),
+                ]),
+            ])
+        )])
+    );
+    assert_eq!(Manifest::deserialize(value)?, manifest);
+    Ok(())
+}
+
+struct ValueSerializer;
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(fn $method(self, $(_: $arg),*) -> Result<$ret, Error> {
+            Err(ser::Error::custom(concat!(stringify!($method), " is unsupported")))
+        })*
+    };
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = Impossible<Value, Error>;
+    type SerializeTupleStruct = Impossible<Value, Error>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = Impossible<Value, Error>;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::U64(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::Str(v.to_string()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Value::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer(vec![]))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer(vec![]))
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> Value;
+        serialize_i8(i8) -> Value;
+        serialize_i16(i16) -> Value;
+        serialize_i32(i32) -> Value;
+        serialize_i64(i64) -> Value;
+        serialize_u8(u8) -> Value;
+        serialize_u16(u16) -> Value;
+        serialize_u32(u32) -> Value;
+        serialize_f32(f32) -> Value;
+        serialize_f64(f64) -> Value;
+        serialize_char(char) -> Value;
+        serialize_bytes(&[u8]) -> Value;
+        serialize_unit() -> Value;
+        serialize_unit_struct(&'static str) -> Value;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Value;
+        serialize_tuple(usize) -> Impossible<Value, Error>;
+        serialize_tuple_struct(&'static str, usize) -> Impossible<Value, Error>;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Impossible<Value, Error>;
+        serialize_map(Option<usize>) -> Impossible<Value, Error>;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Impossible<Value, Error>;
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, _: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(ser::Error::custom(
+            "serialize_newtype_struct is unsupported",
+        ))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(ser::Error::custom(
+            "serialize_newtype_variant is unsupported",
+        ))
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+struct StructSerializer(Vec<(String, Value)>);
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0
+            .push((key.to_string(), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Map(self.0))
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::Str(v) => visitor.visit_string(v),
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(*v),
+            Value::Seq(v) => visitor.visit_seq(SeqDeserializer::new(v.into_iter())),
+            Value::Map(v) => visitor.visit_map(MapDeserializer::new(v.into_iter())),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
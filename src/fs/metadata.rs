@@ -1,4 +1,4 @@
-use anyhow::Context;
+use crate::context::with_path_context;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::time::SystemTime;
@@ -13,23 +13,17 @@ pub struct Metadata {
 impl Metadata {
     /// Extend [std::fs::Metadata::modified] to provide the path as error context
     pub fn modified(&self) -> anyhow::Result<SystemTime> {
-        self.md
-            .modified()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+        with_path_context(self.md.modified(), &self.path)
     }
 
     /// Extend [std::fs::Metadata::accessed] to provide the path as error context
     pub fn accessed(&self) -> anyhow::Result<SystemTime> {
-        self.md
-            .accessed()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+        with_path_context(self.md.accessed(), &self.path)
     }
 
     /// Extend [std::fs::Metadata::created] to provide the path as error context
     pub fn created(&self) -> anyhow::Result<SystemTime> {
-        self.md
-            .created()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+        with_path_context(self.md.created(), &self.path)
     }
 }
 
@@ -0,0 +1,188 @@
+use crate::audit::audited;
+use crate::context::{value_context, with_path_context};
+use anyhow::Context;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+/// Extend [std::fs::OpenOptions] with [anyhow] methods
+///
+/// [OpenOptions] does not expose which options are set, so only the path is provided as error
+/// context, and every open is recorded as a `write` by the [audit](crate::audit) recorder, even a
+/// read-only one. Use [OpenOptionsBuilder] to include the options as well.
+pub trait OpenOptionsAnyhow {
+    /// Wrap [OpenOptions::open], providing the path as error context
+    fn open_anyhow<P>(&self, path: P) -> anyhow::Result<File>
+    where
+        P: AsRef<Path>;
+}
+
+impl OpenOptionsAnyhow for OpenOptions {
+    fn open_anyhow<P>(&self, path: P) -> anyhow::Result<File>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        audited("write", &[path], || {
+            with_path_context(self.open(path), path)
+        })
+    }
+}
+
+/// Mirror [std::fs::OpenOptions], providing the path and the enabled options as error context
+///
+/// ```
+/// use anyhow_std::fs::OpenOptionsBuilder;
+///
+/// let res = OpenOptionsBuilder::new().write(true).create(true).open_anyhow("/proc/no/such/file");
+/// assert_eq!(
+///     format!("{:#}", res.err().unwrap()),
+///     r#"while processing path "/proc/no/such/file": with options write, create: No such file or directory (os error 2)"#,
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenOptionsBuilder {
+    inner: OpenOptions,
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    #[cfg(target_family = "unix")]
+    mode: Option<u32>,
+}
+
+impl Default for OpenOptionsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenOptionsBuilder {
+    /// Create a builder with the [std::fs::OpenOptions::new] defaults
+    pub fn new() -> Self {
+        OpenOptionsBuilder {
+            inner: OpenOptions::new(),
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            #[cfg(target_family = "unix")]
+            mode: None,
+        }
+    }
+
+    /// Wrap [std::fs::OpenOptions::read]
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.inner.read(read);
+        self.read = read;
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::write]
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.inner.write(write);
+        self.write = write;
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::append]
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.inner.append(append);
+        self.append = append;
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::truncate]
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.inner.truncate(truncate);
+        self.truncate = truncate;
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::create]
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.inner.create(create);
+        self.create = create;
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::create_new]
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.inner.create_new(create_new);
+        self.create_new = create_new;
+        self
+    }
+
+    /// Wrap [OpenOptionsExt::mode](std::os::unix::fs::OpenOptionsExt::mode)
+    #[cfg(target_family = "unix")]
+    pub fn mode(&mut self, mode: u32) -> &mut Self {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        self.inner.mode(mode);
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Wrap [std::fs::OpenOptions::open], providing the path and the enabled options as error
+    /// context
+    ///
+    /// An open with any option besides `read` enabled can modify the file, so it is recorded as a
+    /// `write` by the [audit](crate::audit) recorder, as [OpenOptionsAnyhow::open_anyhow] records
+    /// every open.
+    pub fn open_anyhow<P>(&self, path: P) -> anyhow::Result<File>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let open = || {
+            with_path_context(
+                self.inner
+                    .open(path)
+                    .with_context(|| value_context("options", self.describe_options())),
+                path,
+            )
+        };
+        if self.can_modify() {
+            audited("write", &[path], open)
+        } else {
+            open()
+        }
+    }
+
+    fn can_modify(&self) -> bool {
+        self.write || self.append || self.truncate || self.create || self.create_new
+    }
+
+    /// List the enabled options, such as `read, create`
+    fn describe_options(&self) -> String {
+        let flags = [
+            (self.read, "read"),
+            (self.write, "write"),
+            (self.append, "append"),
+            (self.truncate, "truncate"),
+            (self.create, "create"),
+            (self.create_new, "create_new"),
+        ];
+        let mut options: Vec<String> = flags
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, name)| name.to_string())
+            .collect();
+        #[cfg(target_family = "unix")]
+        if let Some(mode) = self.mode {
+            options.push(format!("mode {:#o}", mode));
+        }
+
+        if options.is_empty() {
+            "none".to_string()
+        } else {
+            options.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,86 @@
+use crate::audit::record;
+use crate::fs::{OpenOptionsAnyhow, OpenOptionsBuilder};
+use crate::testutils::{err_str, stringify_error};
+use std::fs::OpenOptions;
+use test_case::test_case;
+
+#[test_case(
+    OpenOptionsBuilder::new().read(true)
+    => err_str(r#"while processing path "/this/path/should/not/exist": with options read: No such file or directory (os error 2)"#)
+    ; "read"
+)]
+#[test_case(
+    OpenOptionsBuilder::new().write(true).create(true).truncate(true)
+    => err_str(r#"while processing path "/this/path/should/not/exist": with options write, truncate, create: No such file or directory (os error 2)"#)
+    ; "write create truncate"
+)]
+#[test_case(
+    OpenOptionsBuilder::new().append(true).create_new(true)
+    => err_str(r#"while processing path "/this/path/should/not/exist": with options append, create_new: No such file or directory (os error 2)"#)
+    ; "append create new"
+)]
+#[test_case(
+    &OpenOptionsBuilder::new()
+    => err_str(r#"while processing path "/this/path/should/not/exist": with options none: must specify at least one of read, write, or append access"#)
+    ; "none"
+)]
+fn open(opts: &OpenOptionsBuilder) -> Result<(), String> {
+    stringify_error(opts.open_anyhow("/this/path/should/not/exist").map(|_| ()))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn open_with_mode() {
+    let err = OpenOptionsBuilder::new()
+        .write(true)
+        .create(true)
+        .mode(0o600)
+        .open_anyhow("/this/path/should/not/exist")
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        r#"while processing path "/this/path/should/not/exist": with options write, create, mode 0o600: No such file or directory (os error 2)"#
+    );
+}
+
+#[test]
+fn open_std_options() {
+    let res = OpenOptions::new()
+        .read(true)
+        .open_anyhow("/this/path/should/not/exist");
+    assert_eq!(
+        stringify_error(res.map(|_| ())),
+        err_str(
+            r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        )
+    );
+}
+
+#[test]
+fn open_records_writes() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let built = dir.path().join("built");
+    let plain = dir.path().join("plain");
+
+    let (res, records) = record(|| -> anyhow::Result<()> {
+        OpenOptionsBuilder::new()
+            .write(true)
+            .create(true)
+            .open_anyhow(&built)?;
+        OpenOptionsBuilder::new().read(true).open_anyhow(&built)?;
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open_anyhow(&plain)?;
+        Ok(())
+    });
+    res?;
+
+    let ops: Vec<_> = records
+        .iter()
+        .map(|r| (r.operation, r.paths.clone()))
+        .collect();
+    assert_eq!(ops, [("write", vec![built]), ("write", vec![plain])]);
+    Ok(())
+}
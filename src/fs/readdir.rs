@@ -1,3 +1,4 @@
+use crate::context::read_dir_context;
 use crate::fs::DirEntry;
 use anyhow::Context;
 use std::path::{Path, PathBuf};
@@ -33,7 +34,7 @@ fn wrap_read_dir_item(
     item.map(|stditem| {
         stditem
             .map(DirEntry::from)
-            .with_context(|| format!("while reading directory {:?}", path.display()))
+            .with_context(|| read_dir_context(path))
     })
 }
 
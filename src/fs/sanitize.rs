@@ -0,0 +1,75 @@
+use std::fmt;
+
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A change made by [sanitize_file_name_anyhow]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameChange {
+    /// A path separator, a character Windows forbids, or a control character was replaced with `_`
+    ReplacedChar {
+        /// The character index in the original string
+        index: usize,
+        /// The replaced character
+        original: char,
+    },
+    /// Trailing dots or spaces, which Windows silently drops, were removed
+    TrimmedTrailing(String),
+    /// The name was a reserved Windows device name, such as `NUL`, and was prefixed with `_`
+    ReservedName(String),
+}
+
+impl fmt::Display for NameChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NameChange::ReplacedChar { index, original } => {
+                write!(f, "replaced {original:?} at index {index}")
+            }
+            NameChange::TrimmedTrailing(s) => write!(f, "trimmed trailing {s:?}"),
+            NameChange::ReservedName(s) => write!(f, "prefixed reserved name {s:?}"),
+        }
+    }
+}
+
+/// Convert an arbitrary string, such as a user-supplied title, into a file name which is safe on
+/// all common platforms
+///
+/// Returns the sanitized name along with every [NameChange] made, so callers can log them. It is
+/// an error for the result to be empty, as for `""`, `"."`, or `".."`.
+pub fn sanitize_file_name_anyhow(s: &str) -> anyhow::Result<(String, Vec<NameChange>)> {
+    let mut changes = vec![];
+    let mut name: String = s
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            if c.is_control() || matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*') {
+                changes.push(NameChange::ReplacedChar { index, original: c });
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let trimmed_len = name.trim_end_matches(['.', ' ']).len();
+    if trimmed_len < name.len() {
+        changes.push(NameChange::TrimmedTrailing(name.split_off(trimmed_len)));
+    }
+
+    if name.is_empty() {
+        anyhow::bail!("file name {:?} is empty after sanitizing", s);
+    }
+
+    let base = name.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        changes.push(NameChange::ReservedName(base.to_string()));
+        name.insert(0, '_');
+    }
+
+    Ok((name, changes))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use crate::fs::{sanitize_file_name_anyhow, NameChange};
+use crate::testutils::{err_str, stringify_error};
+use test_case::test_case;
+
+#[test_case("report" => Ok("report".to_string()); "unchanged")]
+#[test_case("a/b\\c" => Ok("a_b_c".to_string()); "separators")]
+#[test_case("what? <now>: \"x\" | *" => Ok("what_ _now__ _x_ _ _".to_string()); "windows forbidden")]
+#[test_case("tab\tnul\0" => Ok("tab_nul_".to_string()); "control chars")]
+#[test_case("notes. . " => Ok("notes".to_string()); "trailing dots and spaces")]
+#[test_case("nul.txt" => Ok("_nul.txt".to_string()); "reserved with extension")]
+#[test_case("COM1" => Ok("_COM1".to_string()); "reserved")]
+#[test_case("console" => Ok("console".to_string()); "reserved prefix only")]
+#[test_case("" => err_str(r#"file name "" is empty after sanitizing"#); "empty")]
+#[test_case(".." => err_str(r#"file name ".." is empty after sanitizing"#); "dot dot")]
+fn sanitize(s: &str) -> Result<String, String> {
+    stringify_error(sanitize_file_name_anyhow(s).map(|(name, _)| name))
+}
+
+#[test]
+fn sanitize_changes() -> anyhow::Result<()> {
+    let (name, changes) = sanitize_file_name_anyhow("aux.a/b. ")?;
+    assert_eq!(name, "_aux.a_b");
+    assert_eq!(
+        changes,
+        [
+            NameChange::ReplacedChar {
+                index: 5,
+                original: '/'
+            },
+            NameChange::TrimmedTrailing(". ".to_string()),
+            NameChange::ReservedName("aux".to_string()),
+        ]
+    );
+    assert_eq!(
+        changes.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+        [
+            r#"replaced '/' at index 5"#,
+            r#"trimmed trailing ". ""#,
+            r#"prefixed reserved name "aux""#,
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use crate::context::{render_path, with_path_context};
+use crate::fs::handles::{open_dir, open_handle_limit};
+use crate::fs::{DirEntry, ReadDir};
+use crate::PathAnyhow;
+use std::collections::VecDeque;
+use std::fs::FileType;
+use std::path::{Path, PathBuf};
+
+/// A depth-first traversal of the entries below a directory, yielding parents before children
+///
+/// Returned by [PathAnyhow::walk_anyhow]. By default, symbolic
+/// links are yielded but not followed and there is no depth limit. Errors reading a subdirectory
+/// are yielded after the subdirectory's own entry, and the traversal continues with its
+/// siblings. At most [open_handle_limit] directory handles are open
+/// at once. The traversal ends with a [Cancelled](crate::cancel::Cancelled) error once the current
+/// [CancelToken](crate::cancel::CancelToken) is cancelled.
+#[derive(Debug)]
+pub struct Walk {
+    stack: Vec<Frame>,
+    root: PathBuf,
+    pending: Option<anyhow::Error>,
+    limit: usize,
+    max_depth: usize,
+    follow: bool,
+    yielded: u64,
+}
+
+/// A directory being traversed, with its canonical path when following symbolic links
+#[derive(Debug)]
+struct Frame {
+    level: Level,
+    canonical: Option<PathBuf>,
+}
+
+/// A directory's entries, either still open or with the remaining entries buffered
+#[derive(Debug)]
+enum Level {
+    Open(ReadDir),
+    Buffered(VecDeque<anyhow::Result<DirEntry>>),
+}
+
+impl Iterator for Level {
+    type Item = anyhow::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Level::Open(rd) => rd.next(),
+            Level::Buffered(entries) => entries.pop_front(),
+        }
+    }
+}
+
+impl Walk {
+    pub(crate) fn new(root: &Path) -> anyhow::Result<Self> {
+        Self::with_limit(root, open_handle_limit())
+    }
+
+    pub(crate) fn with_limit(root: &Path, limit: usize) -> anyhow::Result<Self> {
+        Ok(Walk {
+            stack: vec![Frame {
+                level: Level::Open(open_dir(root)?),
+                canonical: None,
+            }],
+            root: root.to_path_buf(),
+            pending: None,
+            limit: limit.max(1),
+            max_depth: usize::MAX,
+            follow: false,
+            yielded: 0,
+        })
+    }
+
+    /// Yield entries at most `depth` levels below the root, where the root's own entries are at
+    /// depth 1
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Descend into symbolic links to directories
+    ///
+    /// A link resolving to a directory being traversed yields an error naming both, instead of
+    /// looping forever.
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        if let (true, Some(frame)) = (follow, self.stack.first_mut()) {
+            match self.root.canonicalize_anyhow() {
+                Ok(canonical) => frame.canonical = Some(canonical),
+                Err(e) => self.pending = Some(e),
+            }
+        }
+        self
+    }
+
+    fn push(&mut self, rd: ReadDir, canonical: Option<PathBuf>) {
+        let is_open = |f: &Frame| matches!(f.level, Level::Open(_));
+        if self.stack.iter().filter(|f| is_open(f)).count() >= self.limit {
+            // Close the outermost open handle; it is resumed from memory later.
+            if let Some(frame) = self.stack.iter_mut().find(|f| is_open(f)) {
+                frame.level = Level::Buffered(frame.level.by_ref().collect());
+            }
+        }
+        self.stack.push(Frame {
+            level: Level::Open(rd),
+            canonical,
+        });
+    }
+
+    /// Descend into `path` if it is a directory to traverse
+    fn descend(&mut self, path: &Path, ft: FileType) -> anyhow::Result<()> {
+        let is_dir = ft.is_dir()
+            || (self.follow && ft.is_symlink() && path.metadata().is_ok_and(|md| md.is_dir()));
+        if !is_dir || self.stack.len() >= self.max_depth {
+            return Ok(());
+        }
+
+        let canonical = if self.follow {
+            let canonical = path.canonicalize_anyhow()?;
+            if self
+                .stack
+                .iter()
+                .any(|f| f.canonical.as_ref() == Some(&canonical))
+            {
+                return with_path_context(
+                    Err(anyhow::anyhow!(
+                        "symbolic link loop to ancestor directory {}",
+                        render_path(&canonical)
+                    )),
+                    path,
+                );
+            }
+            Some(canonical)
+        } else {
+            None
+        };
+        let rd = open_dir(path)?;
+        self.push(rd, canonical);
+        Ok(())
+    }
+}
+
+impl Iterator for Walk {
+    type Item = anyhow::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.pending.take() {
+            return Some(Err(e));
+        }
+        if self.max_depth == 0 {
+            return None;
+        }
+        if let Err(e) = crate::cancel::check_entries(self.yielded) {
+            self.stack.clear();
+            return Some(Err(e));
+        }
+
+        loop {
+            let entry = match self.stack.last_mut()?.level.next() {
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(entry)) => entry,
+            };
+
+            let res = entry
+                .file_type()
+                .and_then(|ft| self.descend(&entry.path(), ft));
+            if let Err(e) = res {
+                self.pending = Some(e);
+            }
+            self.yielded += 1;
+            return Some(Ok(entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,137 @@
+use crate::fs::handles::annotate_exhaustion;
+use crate::fs::Walk;
+use crate::PathAnyhow;
+use std::path::Path;
+use test_case::test_case;
+
+fn walked(root: &Path, limit: usize) -> anyhow::Result<Vec<String>> {
+    names(root, Walk::with_limit(root, limit)?)
+}
+
+fn names(root: &Path, walk: Walk) -> anyhow::Result<Vec<String>> {
+    let mut names = walk
+        .map(|entry| {
+            let path = entry?.path();
+            Ok(path.strip_prefix_anyhow(root)?.display().to_string())
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    names.sort();
+    Ok(names)
+}
+
+#[test_case(1; "one handle")]
+#[test_case(2; "two handles")]
+#[test_case(64; "default")]
+fn limit_preserves_entries(limit: usize) -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    dir.path().join("a/b/c").create_dir_all_anyhow()?;
+    dir.path().join("a/b/c/leaf").write_anyhow("")?;
+    dir.path().join("a/sibling").write_anyhow("")?;
+    dir.path().join("top").write_anyhow("")?;
+
+    assert_eq!(
+        walked(dir.path(), limit)?,
+        ["a", "a/b", "a/b/c", "a/b/c/leaf", "a/sibling", "top"]
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn exhaustion_annotated() {
+    let err = annotate_exhaustion::<()>(Err(std::io::Error::from_raw_os_error(24)))
+        .err()
+        .unwrap();
+    let msg = format!("{:#}", err);
+    assert!(msg.starts_with("with open file limit "), "{}", msg);
+    assert!(
+        msg.ends_with(
+            "; raise it with \"ulimit -n\" or lower anyhow_std::fs::set_open_handle_limit: Too many open files (os error 24)"
+        ),
+        "{}",
+        msg
+    );
+
+    let err = annotate_exhaustion::<()>(Err(std::io::Error::from_raw_os_error(2)))
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        "No such file or directory (os error 2)"
+    );
+}
+
+#[test_case(0 => Vec::<String>::new(); "zero")]
+#[test_case(1 => vec!["a", "top"]; "one")]
+#[test_case(2 => vec!["a", "a/b", "a/sibling", "top"]; "two")]
+fn max_depth(depth: usize) -> Vec<String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    dir.path().join("a/b/c").create_dir_all_anyhow().unwrap();
+    dir.path().join("a/sibling").write_anyhow("").unwrap();
+    dir.path().join("top").write_anyhow("").unwrap();
+
+    let walk = dir.path().walk_anyhow().unwrap().max_depth(depth);
+    names(dir.path(), walk).unwrap()
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn follow_symlinks() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path().join("root");
+    root.join("real").create_dir_all_anyhow()?;
+    root.join("real/file").write_anyhow("")?;
+    std::os::unix::fs::symlink(root.join("real"), root.join("link"))?;
+
+    let walk = root.walk_anyhow()?;
+    assert_eq!(names(&root, walk)?, ["link", "real", "real/file"]);
+
+    let walk = root.walk_anyhow()?.follow_symlinks(true);
+    assert_eq!(
+        names(&root, walk)?,
+        ["link", "link/file", "real", "real/file"]
+    );
+
+    std::os::unix::fs::symlink(&root, root.join("real/loop"))?;
+    let mut errors: Vec<String> = root
+        .walk_anyhow()?
+        .follow_symlinks(true)
+        .filter_map(|e| e.err())
+        .map(|e| format!("{:#}", e).replace(&root.display().to_string(), "ROOT"))
+        .collect();
+    errors.sort();
+    assert_eq!(
+        errors,
+        [
+            r#"while processing path "ROOT/link/loop": symbolic link loop to ancestor directory "ROOT""#,
+            r#"while processing path "ROOT/real/loop": symbolic link loop to ancestor directory "ROOT""#,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn follow_symlinks_after_exhaustion() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let mut walk = Walk::new(dir.path())?;
+    assert!(walk.next().is_none());
+    assert!(walk.follow_symlinks(true).next().is_none());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn follow_symlinks_root_removed() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path().join("root");
+    root.create_dir_anyhow()?;
+    let walk = Walk::new(&root)?;
+    root.remove_dir_anyhow()?;
+
+    let err = walk.follow_symlinks(true).next().unwrap().err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR"),
+        r#"while processing path "DIR/root": No such file or directory (os error 2)"#
+    );
+    Ok(())
+}
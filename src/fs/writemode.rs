@@ -0,0 +1,45 @@
+use std::fmt;
+use std::fs::OpenOptions;
+
+/// How [PathAnyhow::write_mode_anyhow](crate::PathAnyhow::write_mode_anyhow) treats the file
+///
+/// Each mode fails distinctly when its expectation about the file is not met, so callers can
+/// match on the [std::io::ErrorKind] of the underlying [std::io::Error].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Create the file or truncate existing contents, like [std::fs::write]
+    Truncate,
+    /// Create the file or append to existing contents
+    Append,
+    /// Create a new file, failing with [AlreadyExists](std::io::ErrorKind::AlreadyExists) if it
+    /// exists
+    CreateNew,
+    /// Truncate an existing file, failing with [NotFound](std::io::ErrorKind::NotFound) if it does
+    /// not exist
+    MustExist,
+}
+
+impl WriteMode {
+    /// The [OpenOptions] implementing this mode
+    pub fn open_options(self) -> OpenOptions {
+        let mut opts = OpenOptions::new();
+        match self {
+            WriteMode::Truncate => opts.write(true).create(true).truncate(true),
+            WriteMode::Append => opts.append(true).create(true),
+            WriteMode::CreateNew => opts.write(true).create_new(true),
+            WriteMode::MustExist => opts.write(true).truncate(true),
+        };
+        opts
+    }
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            WriteMode::Truncate => "truncate",
+            WriteMode::Append => "append",
+            WriteMode::CreateNew => "create-new",
+            WriteMode::MustExist => "must-exist",
+        })
+    }
+}
@@ -0,0 +1,122 @@
+//! Fetching files over HTTP with the URL and destination in error contexts
+//!
+//! This module requires the `ureq` feature; `download_verified_anyhow` also requires `sha2`.
+
+use crate::context::{value_context, with_path_context};
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Download `url` to `path`, returning the number of bytes written
+///
+/// The response body is streamed to a temporary sibling of `path`, which is renamed over `path`
+/// once the download completes, so `path` never holds a partial download. Every failure, whether
+/// connecting, an unsuccessful status code, writing, or renaming, has both `url` and `path` in
+/// its context. The download stops with a [Cancelled](crate::cancel::Cancelled) error once the
+/// current [CancelToken](crate::cancel::CancelToken) is cancelled.
+pub fn download_to_anyhow<P>(url: &str, path: P) -> anyhow::Result<u64>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    with_download_context(download(url, path, |_| Ok(())), url, path)
+}
+
+/// Download `url` to `path` as [download_to_anyhow] does, verifying the SHA-256 digest
+///
+/// `expected_sha256` is compared case-insensitively against the hex digest of the downloaded
+/// content before it is moved into place. On a mismatch, the error contains both digests and the
+/// partial download is removed, leaving `path` untouched.
+#[cfg(feature = "sha2")]
+pub fn download_verified_anyhow<P>(url: &str, path: P, expected_sha256: &str) -> anyhow::Result<u64>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    with_download_context(
+        download(url, path, |tmp| verify_sha256(tmp, expected_sha256)),
+        url,
+        path,
+    )
+}
+
+/// Add `path` and, inside it, `url` as error context
+fn with_download_context<T>(res: anyhow::Result<T>, url: &str, path: &Path) -> anyhow::Result<T> {
+    with_path_context(
+        res.with_context(|| value_context("url", format_args!("{:?}", url))),
+        path,
+    )
+}
+
+#[cfg(feature = "sha2")]
+fn verify_sha256(path: &Path, expected: &str) -> anyhow::Result<()> {
+    let actual = crate::fs::sha256_hex(path)?;
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "SHA-256 mismatch: expected {}, actual {}",
+            expected,
+            actual
+        ))
+    }
+}
+
+/// Download to a temporary sibling of `path`, `verify` it, then rename it over `path`
+fn download<F>(url: &str, path: &Path, verify: F) -> anyhow::Result<u64>
+where
+    F: FnOnce(&Path) -> anyhow::Result<()>,
+{
+    let response = match ureq::get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, response)) => {
+            anyhow::bail!("HTTP status {} {}", code, response.status_text())
+        }
+        Err(e) => return Err(e).context("while connecting"),
+    };
+
+    let tmp = crate::path::temp_sibling(path, ".download")?;
+    let res = (|| {
+        let mut file = with_path_context(OpenOptions::new().write(true).open(&tmp), &tmp)?;
+        let n = with_path_context(
+            copy_cancellable(&mut response.into_reader(), &mut file),
+            &tmp,
+        )?;
+        with_path_context(file.sync_all(), &tmp)?;
+        drop(file);
+        verify(&tmp)?;
+        tmp.rename_anyhow(path)?;
+        Ok(n)
+    })();
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    res
+}
+
+/// Copy like [std::io::copy], checking the current [CancelToken](crate::cancel::CancelToken)
+/// between reads
+fn copy_cancellable<R, W>(r: &mut R, w: &mut W) -> anyhow::Result<u64>
+where
+    R: Read,
+    W: Write,
+{
+    let mut buf = vec![0; 64 * 1024];
+    let mut copied = 0;
+    loop {
+        crate::cancel::check_bytes(copied)?;
+        let n = match r.read(&mut buf) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        w.write_all(&buf[..n])?;
+        copied += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests;
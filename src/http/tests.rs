@@ -0,0 +1,121 @@
+use crate::http::download_to_anyhow;
+use crate::PathAnyhow;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+/// Serve a single HTTP request with `response`, returning the URL to request
+fn serve_once(response: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 4096];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    format!("http://{}/file.txt", addr)
+}
+
+#[test]
+fn download() -> anyhow::Result<()> {
+    let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file.txt");
+    let unrelated = dir.path().join("file.txt.download");
+    unrelated.write_anyhow("kept")?;
+
+    assert_eq!(download_to_anyhow(&url, &path)?, 5);
+    assert_eq!(path.read_to_string_anyhow()?, "hello");
+    assert_eq!(unrelated.read_to_string_anyhow()?, "kept");
+    assert_eq!(dir.path().read_dir_anyhow()?.count(), 2);
+    Ok(())
+}
+
+#[test]
+fn download_status_error() -> anyhow::Result<()> {
+    let url =
+        serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file.txt");
+
+    let err = download_to_anyhow(&url, &path).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        format!(
+            "while processing path {:?}: with url {:?}: HTTP status 404 Not Found",
+            path.display(),
+            url
+        )
+    );
+    assert!(!path.exists_no_follow_anyhow()?);
+    Ok(())
+}
+
+#[test]
+fn download_write_error() -> anyhow::Result<()> {
+    let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+    let path = std::path::Path::new("/this/path/should/not/exist/file.txt");
+
+    let msg = format!("{:#}", download_to_anyhow(&url, path).err().unwrap());
+    let prefix = format!(
+        r#"while processing path "/this/path/should/not/exist/file.txt": with url {:?}: while processing path "/this/path/should/not/exist/file.txt": while processing path "/this/path/should/not/exist/.file.txt."#,
+        url,
+    );
+    assert!(msg.starts_with(&prefix), "{msg}");
+    assert!(
+        msg.ends_with(r#".download": No such file or directory (os error 2)"#),
+        "{msg}"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn download_verified() -> anyhow::Result<()> {
+    use crate::http::download_verified_anyhow;
+
+    const HELLO_SHA256: &str = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+    const RESPONSE: &str = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello";
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file.txt");
+
+    let url = serve_once(RESPONSE);
+    assert_eq!(download_verified_anyhow(&url, &path, HELLO_SHA256)?, 5);
+    assert_eq!(path.read_to_string_anyhow()?, "hello");
+
+    let url = serve_once(RESPONSE);
+    let other = dir.path().join("other.txt");
+    let err = download_verified_anyhow(&url, &other, "00").err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        format!(
+            "while processing path {:?}: with url {:?}: SHA-256 mismatch: expected 00, actual {}",
+            other.display(),
+            url,
+            HELLO_SHA256.to_lowercase(),
+        )
+    );
+    assert_eq!(
+        std::fs::read_dir(dir.path())?.count(),
+        1,
+        "partial download was not removed"
+    );
+    Ok(())
+}
+
+#[test]
+fn download_cancelled() -> anyhow::Result<()> {
+    use crate::cancel::{CancelToken, Cancelled};
+
+    let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file.txt");
+
+    let token = CancelToken::new();
+    token.cancel();
+    let err = token.run(|| download_to_anyhow(&url, &path)).err().unwrap();
+    assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Bytes(0)));
+    assert!(!path.exists_no_follow_anyhow()?);
+    assert!(dir.path().read_dir_anyhow()?.next().is_none());
+    Ok(())
+}
@@ -1,12 +1,25 @@
 #![doc = include_str!("../README.md")]
 
+pub mod audit;
+mod bytesize;
+pub mod cancel;
+pub mod context;
 pub mod env;
+mod errorcollection;
 pub mod fs;
+#[cfg(feature = "ureq")]
+pub mod http;
 mod osstr;
 mod path;
+pub mod prelude;
 pub mod process;
+pub mod report;
+pub mod stdio;
 
-pub use self::osstr::OsStrAnyhow;
+pub use self::bytesize::ByteSize;
+pub use self::errorcollection::{ErrorCollection, ErrorPolicy};
+pub use self::fs::{DirEntryAnyhow, OpenOptionsAnyhow};
+pub use self::osstr::{escape_os, os_string_from_parts_anyhow, OsStrAnyhow};
 pub use self::path::PathAnyhow;
 pub use self::process::CommandAnyhow;
 
@@ -1,6 +1,7 @@
+use crate::context::value_context;
 use anyhow::Context;
 use std::borrow::Cow;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 
 const LONG_STRING_DISPLAY_LIMIT: usize = 80;
 
@@ -23,6 +24,96 @@ impl OsStrAnyhow for OsStr {
     }
 }
 
+/// Join `parts` with `separator`, as for `PATH`-style environment variable values
+///
+/// Parts may mix `&str`, `&OsStr`, `&Path`, and other [`AsRef<OsStr>`](AsRef) types. Each part is
+/// validated first: it is an error for a part to be empty (which `PATH`-style lists interpret as
+/// the current directory), or to contain `separator`, `'\0'`, or `'='`. Errors identify the part
+/// by index and content:
+///
+/// ```
+/// use anyhow_std::os_string_from_parts_anyhow;
+/// use std::path::Path;
+///
+/// let value = os_string_from_parts_anyhow(&[&"/usr/lib", &Path::new("/opt/lib")], ":").unwrap();
+/// assert_eq!(value, "/usr/lib:/opt/lib");
+///
+/// let res = os_string_from_parts_anyhow(&[&"/usr/lib", &"/a:/b"], ":");
+/// assert_eq!(
+///     format!("{:#}", res.err().unwrap()),
+///     r#"with part 1 "/a:/b": contains separator ":""#,
+/// );
+/// ```
+pub fn os_string_from_parts_anyhow(
+    parts: &[&dyn AsRef<OsStr>],
+    separator: &str,
+) -> anyhow::Result<OsString> {
+    let mut joined = OsString::new();
+    for (i, part) in parts.iter().enumerate() {
+        let part = part.as_ref();
+        check_part(part, separator)
+            .with_context(|| value_context(&format!("part {}", i), escape_os(part)))?;
+        if i > 0 {
+            joined.push(separator);
+        }
+        joined.push(part);
+    }
+    Ok(joined)
+}
+
+fn check_part(part: &OsStr, separator: &str) -> anyhow::Result<()> {
+    let bytes = part.as_encoded_bytes();
+    if bytes.is_empty() {
+        anyhow::bail!("is empty");
+    }
+    if !separator.is_empty()
+        && bytes
+            .windows(separator.len())
+            .any(|w| w == separator.as_bytes())
+    {
+        anyhow::bail!("contains separator {:?}", separator);
+    }
+    for c in ['\0', '='] {
+        if bytes.contains(&(c as u8)) {
+            anyhow::bail!("contains {:?}", c);
+        }
+    }
+    Ok(())
+}
+
+/// Render `s` quoted, escaping control characters and quotes and rendering bytes which are not
+/// valid UTF-8 as `\xNN`
+///
+/// This is the rendering used for paths and command arguments in error contexts, so that
+/// binary-unsafe names remain unambiguous:
+///
+/// ```
+/// # #[cfg(unix)]
+/// # {
+/// use std::os::unix::ffi::OsStrExt;
+///
+/// let s = std::ffi::OsStr::from_bytes(b"tab\tand \x81\xFF");
+/// assert_eq!(anyhow_std::escape_os(s), r#""tab\tand \x81\xFF""#);
+/// # }
+/// ```
+pub fn escape_os<S>(s: S) -> String
+where
+    S: AsRef<OsStr>,
+{
+    use std::fmt::Write;
+
+    let mut out = String::from('"');
+    for chunk in s.as_ref().as_encoded_bytes().utf8_chunks() {
+        let valid = format!("{:?}", chunk.valid());
+        out.push_str(&valid[1..valid.len() - 1]);
+        for b in chunk.invalid() {
+            write!(out, "\\x{:02X}", b).unwrap();
+        }
+    }
+    out.push('"');
+    out
+}
+
 fn truncate_long_strings(s: Cow<'_, str>) -> Cow<'_, str> {
     let sref = s.as_ref();
     let charcnt = sref.chars().count();
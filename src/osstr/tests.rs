@@ -1,5 +1,7 @@
+use crate::testutils::{err_str, stringify_error};
 use crate::OsStrAnyhow;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use test_case::test_case;
 
 #[test]
 fn to_str_utf8() -> anyhow::Result<()> {
@@ -31,9 +33,7 @@ fn to_str_invalid_utf8_big() -> anyhow::Result<()> {
 
     let mut v = vec![];
     v.extend(b"invalid utf8 consisting of these ");
-    for _ in 0..1024 {
-        v.push(0xFF);
-    }
+    v.extend([0xFF; 1024]);
     v.extend(b" non-codepoint bytes, which is also a very long string");
 
     let s = OsStr::from_bytes(v.as_slice());
@@ -44,3 +44,42 @@ fn to_str_invalid_utf8_big() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[test_case(&["a"] => Ok(OsString::from("a")); "single")]
+#[test_case(&["a", "b", "c"] => Ok(OsString::from("a:b:c")); "several")]
+#[test_case(&["a", ""] => err_str(r#"with part 1 "": is empty"#); "empty")]
+#[test_case(&["a\0b"] => err_str(r#"with part 0 "a\0b": contains '\0'"#); "nul")]
+#[test_case(&["a", "b", "k=v"] => err_str(r#"with part 2 "k=v": contains '='"#); "equals")]
+fn os_string_from_parts(parts: &[&str]) -> Result<OsString, String> {
+    let parts: Vec<&dyn AsRef<OsStr>> = parts.iter().map(|p| p as &dyn AsRef<OsStr>).collect();
+    stringify_error(crate::os_string_from_parts_anyhow(&parts, ":"))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn os_string_from_parts_mixed() -> anyhow::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    let raw = OsStr::from_bytes(b"\xff");
+    let joined = crate::os_string_from_parts_anyhow(&[&"a", &raw, &Path::new("/p")], ";")?;
+    assert_eq!(joined.as_bytes(), b"a;\xff;/p");
+    Ok(())
+}
+
+#[test_case("plain" => r#""plain""#; "plain")]
+#[test_case("a \"quoted\" \\ path" => r#""a \"quoted\" \\ path""#; "quotes and backslashes")]
+#[test_case("line\nbreak\u{1b}" => r#""line\nbreak\u{1b}""#; "control chars")]
+#[test_case("don't 😀" => r#""don't 😀""#; "unicode")]
+fn escape_os(s: &str) -> String {
+    crate::escape_os(s)
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn escape_os_invalid_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let s = OsStr::from_bytes(b"\x81a\xff\xf0\x9f\x98");
+    assert_eq!(crate::escape_os(s), r#""\x81a\xFF\xF0\x9F\x98""#);
+}
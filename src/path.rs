@@ -1,9 +1,50 @@
-use crate::fs::Metadata;
-use crate::fs::ReadDir;
+use crate::context::{
+    render_path, value_context, with_path_context, with_two_path_context, with_write_context,
+    Operation,
+};
+use crate::fs::{
+    Chunks, CopyOptions, CurrentDirGuard, DirEntry, ExtractReport, FileKind, FollowSymlinks,
+    LineEnding, Manifest, ManifestDiff, Metadata, ReadDir, Walk, WriteMode,
+};
+use crate::{escape_os, ErrorPolicy};
 use anyhow::Context;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fs::{File, Permissions};
+use std::io::IoSlice;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+mod assets;
+mod caserename;
+mod cleanup;
+#[cfg(any(feature = "base64", feature = "hex"))]
+mod codec;
+mod copydir;
+mod duplicates;
+mod entries;
+mod expand;
+mod expect;
+mod largest;
+mod links;
+mod manifest;
+mod moving;
+mod naming;
+mod normalize;
+#[cfg(target_family = "unix")]
+mod perms;
+mod prefix;
+mod relative;
+mod rename_matching;
+mod rotate;
+mod secure;
+mod text;
+mod times;
+mod unique;
+mod write;
+
+pub(crate) use self::secure::join_components;
+#[cfg(feature = "ureq")]
+pub(crate) use self::write::temp_sibling;
 
 /// Extend [Path] with [anyhow] methods
 pub trait PathAnyhow {
@@ -21,27 +62,172 @@ pub trait PathAnyhow {
     where
         P: AsRef<Path>;
 
+    /// Compute the relative path leading from the directory `base` to `self`, both absolute
+    ///
+    /// Unlike [PathAnyhow::strip_prefix_anyhow], `self` need not be below `base`, e.g.
+    /// `/a/b/c` relative to `/a/d` is `../b/c`, and equal paths give `.`. This is lexical, so
+    /// symbolic links are not resolved. It is an error, with both paths as context, if either
+    /// path is relative or no relative path exists, as between different Windows drives.
+    fn relative_to_anyhow<P>(&self, base: P) -> anyhow::Result<PathBuf>
+    where
+        P: AsRef<Path>;
+
     /// Wrap [Path::file_stem], providing the path as error context
     fn file_stem_anyhow(&self) -> anyhow::Result<&OsStr>;
 
     /// Wrap [Path::extension], providing the path as error context
     fn extension_anyhow(&self) -> anyhow::Result<&OsStr>;
 
+    /// Return the file name as a `&str`, with one path context if it is missing or not UTF-8
+    fn file_name_str_anyhow(&self) -> anyhow::Result<&str>;
+
+    /// Return the file stem as a `&str`, with one path context if it is missing or not UTF-8
+    fn file_stem_str_anyhow(&self) -> anyhow::Result<&str>;
+
+    /// Return the extension as a `&str`, with one path context if it is missing or not UTF-8
+    fn extension_str_anyhow(&self) -> anyhow::Result<&str>;
+
+    /// Fail unless the extension is exactly `ext`, naming the expected and actual extensions
+    ///
+    /// `ext` excludes the dot, e.g. `"json"`. A mismatch reads `expected extension "json", found
+    /// "txt"`, or `found none` for a path without an extension.
+    fn expect_extension_anyhow(&self, ext: &str) -> anyhow::Result<()>;
+
+    /// Fail unless the extension equals `ext` ignoring ASCII case, as
+    /// [PathAnyhow::expect_extension_anyhow] does otherwise
+    fn expect_extension_ignore_case_anyhow(&self, ext: &str) -> anyhow::Result<()>;
+
     /// Wrap [Path::metadata], providing the path as error context
     fn metadata_anyhow(&self) -> anyhow::Result<Metadata>;
 
     /// Wrap [Path::symlink_metadata], providing the path as error context
     fn symlink_metadata_anyhow(&self) -> anyhow::Result<Metadata>;
 
+    /// Return the [Metadata] of the path, stating explicitly whether a final symbolic link is
+    /// followed
+    ///
+    /// This is [PathAnyhow::metadata_anyhow] or [PathAnyhow::symlink_metadata_anyhow], with the
+    /// choice included in the error context, e.g. `with follow symlinks no`.
+    fn stat_anyhow(&self, follow: FollowSymlinks) -> anyhow::Result<Metadata>;
+
+    /// Classify the entry at the path, without following a final symbolic link
+    ///
+    /// For symbolic links the kind of the resolved target is included, or `None` if the link
+    /// dangles.
+    fn classify_anyhow(&self) -> anyhow::Result<FileKind>;
+
+    /// Return the [Metadata] of the path, following symbolic links, or an error naming the kind
+    /// found if it is not a directory
+    fn ensure_dir_anyhow(&self) -> anyhow::Result<Metadata>;
+
+    /// Return the [Metadata] of the path, following symbolic links, or an error naming the kind
+    /// found if it is not a regular file
+    fn ensure_file_anyhow(&self) -> anyhow::Result<Metadata>;
+
+    /// Fail unless the file contains exactly `expected`
+    ///
+    /// A mismatch names the first differing line and byte offset, with a short excerpt of each
+    /// side from the start of that line, e.g. `contents differ at line 2, byte offset 9: expected
+    /// "port = 80", found "port = 8080"`. This suits integration tests and config validators.
+    fn expect_contents_anyhow<C>(&self, expected: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>;
+
+    /// Fail unless the file's contents include `needle`
+    fn expect_contains_anyhow<C>(&self, needle: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>;
+
+    /// Recognize the format of the file from its first bytes, such as gzip or a zip archive
+    #[cfg(feature = "sniff")]
+    fn detect_kind_anyhow(&self) -> anyhow::Result<crate::fs::ContentKind>;
+
+    /// Fail unless the file's contents look like `expected`, naming the kind found instead
+    ///
+    /// This lets tools reject a wrong input early, e.g. `expected a zip archive, but the contents
+    /// look like gzip data`.
+    #[cfg(feature = "sniff")]
+    fn expect_kind_anyhow(&self, expected: crate::fs::ContentKind) -> anyhow::Result<()>;
+
+    /// Return whether the path itself exists, without following a final symbolic link
+    ///
+    /// Unlike [Path::exists], a dangling symbolic link exists, and errors other than
+    /// [NotFound](std::io::ErrorKind::NotFound), such as permission errors, are returned rather
+    /// than treated as non-existence.
+    fn exists_no_follow_anyhow(&self) -> anyhow::Result<bool>;
+
+    /// Return `self` if it does not exist, otherwise the first free sibling of the form
+    /// `report (1).txt`, `report (2).txt`, and so on
+    ///
+    /// At most `max_attempts` numbered names are tried. Existence is checked without following
+    /// symbolic links, and another process may still claim the returned name before it is used.
+    fn next_available_name_anyhow(&self, max_attempts: usize) -> anyhow::Result<PathBuf>;
+
+    /// Create a new empty file with a random name `<prefix><random><suffix>` in the directory
+    /// `self`, returning its path
+    ///
+    /// The file is created exclusively, so concurrent callers never receive the same path. The
+    /// error names the directory and the number of attempts if every generated name was taken.
+    fn unique_path_anyhow(&self, prefix: &str, suffix: &str) -> anyhow::Result<PathBuf>;
+
     /// Wrap [Path::canonicalize], providing the path as error context
     fn canonicalize_anyhow(&self) -> anyhow::Result<PathBuf>;
 
+    /// Expand a leading `~` and environment variables from the process environment
+    ///
+    /// `~` alone or followed by a separator becomes the home directory, from `HOME` or, on
+    /// Windows, `USERPROFILE`; `~user` is kept as is. `$NAME` and `${NAME}` are replaced
+    /// everywhere, as is `%NAME%` on Windows, while a `$` or `%` not starting a variable is kept.
+    /// It is an error, naming the variable, for a referenced variable to be unset.
+    fn expand_anyhow(&self) -> anyhow::Result<PathBuf>;
+
+    /// Resolve `.` and `..` components without accessing the filesystem
+    ///
+    /// Unlike [PathAnyhow::canonicalize_anyhow], the path need not exist, and symbolic links are
+    /// not resolved, so `link/..` becomes `.` even if `link` points elsewhere. It is an error for
+    /// `..` to climb above the root of an absolute path or the start of a relative one.
+    fn normalize_lexically_anyhow(&self) -> anyhow::Result<PathBuf>;
+
+    /// Canonicalize `self` even if it does not exist yet, to tell where a file would be created
+    ///
+    /// The longest existing ancestor is canonicalized and the remaining components are
+    /// appended lexically, resolving `.` and `..`. An error resolving an ancestor for a reason
+    /// other than its absence names that ancestor.
+    fn canonicalize_missing_anyhow(&self) -> anyhow::Result<PathBuf>;
+
+    /// Canonicalize both `self` and `base`, returning the canonical `base` and the remainder of
+    /// `self` below it
+    ///
+    /// Unlike [PathAnyhow::strip_prefix_anyhow], symbolic links such as macOS's `/tmp` are
+    /// resolved first, and a mismatch reports both canonical paths and the first component where
+    /// they diverge.
+    fn split_prefix_anyhow<P>(&self, base: P) -> anyhow::Result<(PathBuf, PathBuf)>
+    where
+        P: AsRef<Path>;
+
+    /// Join the `untrusted` relative path onto `self`, guaranteeing the result stays below `self`
+    ///
+    /// This rejects absolute paths, Windows prefixes such as `C:`, and `..` components which would
+    /// climb above `self`, naming the offending component. `..` within `untrusted` is resolved
+    /// lexically, e.g. `a/../b` joins as `b`. Symbolic links are not inspected, so any below
+    /// `self` must be trusted.
+    fn join_secure_anyhow<P>(&self, untrusted: P) -> anyhow::Result<PathBuf>
+    where
+        P: AsRef<Path>;
+
     /// Wrap [Path::read_link], providing the path as error context
     fn read_link_anyhow(&self) -> anyhow::Result<PathBuf>;
 
     /// Wrap [Path::read_dir], providing the path as error context
     fn read_dir_anyhow(&self) -> anyhow::Result<ReadDir>;
 
+    /// Traverse the directory tree below `self` depth-first, as configured by the [Walk]
+    /// builder methods
+    ///
+    /// Errors for individual entries are yielded without ending the traversal, each with the
+    /// path of the directory or entry concerned as context.
+    fn walk_anyhow(&self) -> anyhow::Result<Walk>;
+
     // Wrappers for std::fs:
 
     /// Wrap [std::fs::copy] from `self` to `to`, providing `self` and `to` as error context
@@ -49,6 +235,18 @@ pub trait PathAnyhow {
     where
         P: AsRef<Path>;
 
+    /// Copy the directory tree `self` to `to`, returning the number of file bytes copied
+    ///
+    /// `to` is created if needed, as are the directories below it. Existing files and symbolic
+    /// links are replaced only with [CopyOptions::overwrite], and symbolic links are handled as
+    /// chosen with [CopyOptions::symlinks]. Symbolic links below `to` are never followed: a
+    /// directory whose destination is one fails, and its entries are skipped. Every other entry
+    /// is attempted; failures are aggregated into an [ErrorCollection](crate::ErrorCollection),
+    /// and each names the paths involved.
+    fn copy_dir_all_anyhow<P>(&self, to: P, options: CopyOptions) -> anyhow::Result<u64>
+    where
+        P: AsRef<Path>;
+
     /// Wrap [std::fs::create_dir], providing the path as error context
     fn create_dir_anyhow(&self) -> anyhow::Result<()>;
 
@@ -66,6 +264,61 @@ pub trait PathAnyhow {
     /// Wrap [std::fs::read_to_string], providing the path as error context
     fn read_to_string_anyhow(&self) -> anyhow::Result<String>;
 
+    /// Read the file as text, refusing files larger than `max_bytes`
+    ///
+    /// At most `max_bytes + 1` bytes are read, so an untrusted path cannot exhaust memory. An
+    /// oversized file is an error naming the path, the size, and the limit, each rendered as a
+    /// [ByteSize](crate::ByteSize), e.g. `file is 3.4 GiB, limit is 100 MiB`.
+    fn read_to_string_limited_anyhow(&self, max_bytes: u64) -> anyhow::Result<String>;
+
+    /// Read the file as text, replacing invalid UTF-8 sequences with `U+FFFD`
+    ///
+    /// Returns the text along with the byte offset in the file of each replaced sequence. If
+    /// `max_replacements` is given and more sequences need replacing, an error with the count and
+    /// offsets is returned instead.
+    fn read_to_string_lossy_anyhow(
+        &self,
+        max_replacements: Option<usize>,
+    ) -> anyhow::Result<(String, Vec<usize>)>;
+
+    /// Read the file as text, detecting and stripping a UTF-8 or UTF-16 byte order mark
+    ///
+    /// Files without a byte order mark are decoded as UTF-8. Invalid sequences are errors
+    /// including the byte offset in the file.
+    #[cfg(feature = "encoding")]
+    fn read_text_anyhow(&self) -> anyhow::Result<String>;
+
+    /// Read the file as text like [PathAnyhow::read_text_anyhow], decoding files without a byte
+    /// order mark as `fallback`
+    #[cfg(feature = "encoding")]
+    fn read_text_encoded_anyhow(
+        &self,
+        fallback: &'static encoding_rs::Encoding,
+    ) -> anyhow::Result<String>;
+
+    /// Read the file as hex digits, ignoring trailing whitespace
+    ///
+    /// Decode errors include the path and the character offset of the first invalid digit.
+    #[cfg(feature = "hex")]
+    fn read_hex_anyhow(&self) -> anyhow::Result<Vec<u8>>;
+
+    /// Write `bytes` to the file as lowercase hex digits followed by a newline
+    #[cfg(feature = "hex")]
+    fn write_hex_anyhow(&self, bytes: &[u8]) -> anyhow::Result<()>;
+
+    /// Read the file as standard padded base64, ignoring trailing whitespace
+    ///
+    /// Decode errors include the path and the byte offset of the first invalid symbol.
+    #[cfg(feature = "base64")]
+    fn read_base64_anyhow(&self) -> anyhow::Result<Vec<u8>>;
+
+    /// Write `bytes` to the file as standard padded base64 followed by a newline
+    #[cfg(feature = "base64")]
+    fn write_base64_anyhow(&self, bytes: &[u8]) -> anyhow::Result<()>;
+
+    /// Read the file as UTF-8 text, normalizing `\r\n` line endings to `\n`
+    fn read_text_normalized_anyhow(&self) -> anyhow::Result<String>;
+
     /// Wrap [std::fs::remove_dir], providing the path as error context
     fn remove_dir_anyhow(&self) -> anyhow::Result<()>;
 
@@ -80,6 +333,216 @@ pub trait PathAnyhow {
     where
         P: AsRef<Path>;
 
+    /// Rename `self` to `to` where the file names may differ only by case
+    ///
+    /// On case-insensitive filesystems a direct rename from `foo.txt` to `Foo.txt` may silently
+    /// do nothing or fail, so when the names differ only by case this renames through a temporary
+    /// sibling name in two steps, with the failing step in the error context. If the second step
+    /// fails, the file is renamed back to `self`; should that fail too, the error names the
+    /// temporary path where the file remains. Otherwise this is equivalent to
+    /// [PathAnyhow::rename_anyhow].
+    fn rename_changing_case_anyhow<P>(&self, to: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>;
+
+    /// Move `self` to `to`, even across filesystems
+    ///
+    /// This renames when possible. If the rename fails because `to` is on another device, `self`
+    /// is copied to `to`, recursively for directories and recreating symbolic links, and then
+    /// removed. The failing phase is named in the error context, for example `while moving path
+    /// "a" to "b": with phase copy: …`.
+    fn move_anyhow<P>(&self, to: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>;
+
+    /// Rename entries of the directory `self` according to `rename`
+    ///
+    /// `rename` is called with each entry's file name and returns the new file name, or `None` to
+    /// leave the entry alone. Before renaming anything, every planned rename is checked and all
+    /// collisions are reported together: two entries renamed to the same name, an entry renamed to
+    /// the name of any existing entry, or a new name which is not a plain file name. If there are
+    /// no collisions, every rename is attempted and any failures are aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection). On success, the number of renamed entries is
+    /// returned.
+    fn rename_matching_anyhow<F>(&self, rename: F) -> anyhow::Result<usize>
+    where
+        F: FnMut(&OsStr) -> Option<OsString>;
+
+    /// Call `f` with each entry of the directory `self`
+    ///
+    /// Errors returned by `f` gain the directory and the entry's file name as context, for example
+    /// `while processing path "dir": with entry "name": …`, and errors iterating the directory
+    /// name the directory. With [ErrorPolicy::FailFast] the first error is returned immediately;
+    /// with [ErrorPolicy::Collect] every entry is visited and all errors are returned together in
+    /// an [ErrorCollection](crate::ErrorCollection).
+    fn for_each_entry_anyhow<F>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<()>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<()>;
+
+    /// Call `f` with each entry of the directory `self`, collecting the results into a [Vec]
+    ///
+    /// Errors are contextualized and handled according to `policy` as in
+    /// [PathAnyhow::for_each_entry_anyhow].
+    fn map_dir_anyhow<F, T>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<T>;
+
+    /// Call `f` with each entry of the directory `self`, collecting the results into any
+    /// [FromIterator] type
+    ///
+    /// Errors are contextualized and handled according to `policy` as in
+    /// [PathAnyhow::for_each_entry_anyhow].
+    fn collect_dir_anyhow<F, T, C>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<C>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<T>,
+        C: FromIterator<T>;
+
+    /// Find symbolic links in the directory tree below `self` whose targets do not resolve
+    ///
+    /// Returns sorted `(link, target)` pairs, where `target` is the unresolved link contents.
+    /// Links are not followed during traversal. Entries which cannot be inspected are skipped and
+    /// their errors aggregated into an [ErrorCollection](crate::ErrorCollection) after the whole
+    /// tree has been scanned.
+    fn find_broken_links_anyhow(&self) -> anyhow::Result<Vec<(PathBuf, PathBuf)>>;
+
+    /// List the regular files in the directory tree below `self` with their sizes
+    ///
+    /// Entry paths are relative to `self` and sorted, so the same tree always produces the same
+    /// [Manifest]. Symbolic links are not followed, and only regular files are listed. Every entry
+    /// is attempted; failures are aggregated into an [ErrorCollection](crate::ErrorCollection).
+    fn manifest_anyhow(&self) -> anyhow::Result<Manifest>;
+
+    /// List the tree as [PathAnyhow::manifest_anyhow] does, with each file's SHA-256 digest
+    ///
+    /// This requires the `sha2` feature.
+    #[cfg(feature = "sha2")]
+    fn manifest_sha256_anyhow(&self) -> anyhow::Result<Manifest>;
+
+    /// Compare the directory tree below `self` against `manifest`
+    ///
+    /// Files are modified when their size differs or, with the `sha2` feature, when the manifest
+    /// records a digest which differs; without `sha2`, a manifest recording digests is an error
+    /// rather than being checked by size alone. Every entry is attempted; read failures are
+    /// aggregated into an [ErrorCollection](crate::ErrorCollection), each with its path as
+    /// context.
+    fn verify_manifest_anyhow(&self, manifest: &Manifest) -> anyhow::Result<ManifestDiff>;
+
+    /// Create a directory with the permission bits `mode`, as modified by the process umask
+    ///
+    /// The mode is included in the error context.
+    #[cfg(target_family = "unix")]
+    fn create_dir_with_mode_anyhow(&self, mode: u32) -> anyhow::Result<()>;
+
+    /// Create a directory accessible only by its owner, for example for runtime or cache data
+    ///
+    /// This is [PathAnyhow::create_dir_with_mode_anyhow] with mode `0o700`.
+    #[cfg(target_family = "unix")]
+    fn create_private_dir_anyhow(&self) -> anyhow::Result<()>;
+
+    /// Set the permission bits of the path to `mode`, e.g. `0o640`
+    ///
+    /// The mode is included in the error context in octal.
+    #[cfg(target_family = "unix")]
+    fn set_mode_anyhow(&self, mode: u32) -> anyhow::Result<()>;
+
+    /// Set the permission bits of `self` and every entry below it to `mode_fn` of the entry's
+    /// kind, like `chmod -R`
+    ///
+    /// Directories and files can thus get different modes. Symbolic links are skipped, since
+    /// setting their mode would change their targets. Every entry is attempted, and failures are
+    /// aggregated into an [ErrorCollection](crate::ErrorCollection). Each directory is opened
+    /// before its mode is changed, so modes which deny reading still apply to the whole tree.
+    #[cfg(target_family = "unix")]
+    fn chmod_recursive_anyhow<F>(&self, mode_fn: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&FileKind) -> u32;
+
+    /// Change the owner and group of `self` and every entry below it, like `chown -R -h`
+    ///
+    /// `None` leaves the owner or group unchanged. Symbolic links themselves are changed, never
+    /// their targets. Returns the sorted paths whose ownership differed; with `dry_run` nothing is
+    /// changed, so this lists what would be. Every entry is attempted, and failures are aggregated
+    /// into an [ErrorCollection](crate::ErrorCollection), each with the requested `uid:gid`.
+    #[cfg(target_family = "unix")]
+    fn chown_recursive_anyhow(
+        &self,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<PathBuf>>;
+
+    /// Read a secret such as a private key, refusing files whose permissions are too open
+    ///
+    /// Like `ssh`, this errors if any permission bit outside `allowed_mode` is set, with the
+    /// actual and allowed modes in the message. `0o600` allows only the owner to read and write.
+    /// The mode is checked on the opened file, so the path cannot be swapped after the check.
+    #[cfg(target_family = "unix")]
+    fn read_secret_anyhow(&self, allowed_mode: u32) -> anyhow::Result<Vec<u8>>;
+
+    /// Find world-writable or setuid entries in the directory tree below `self`
+    ///
+    /// Returns sorted `(path, mode)` pairs, where `mode` holds the permission bits including
+    /// setuid, setgid, and sticky bits. Symbolic links are neither reported nor followed. Entries
+    /// which cannot be inspected are skipped and their errors aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection) after the whole tree has been scanned.
+    #[cfg(target_family = "unix")]
+    fn find_world_writable_anyhow(&self) -> anyhow::Result<Vec<(PathBuf, u32)>>;
+
+    /// Find groups of duplicate files in the directory tree below `self`
+    ///
+    /// Regular, non-empty files are grouped by size and then by a hash of their contents; symbolic
+    /// links are not followed. Each returned group holds at least two paths, sorted, and the
+    /// groups are sorted by their first path. The content hash is not cryptographic, so callers
+    /// about to delete duplicates may want to compare contents directly first.
+    ///
+    /// Entries which cannot be read are skipped and their errors aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection) after the whole tree has been scanned.
+    fn find_duplicates_anyhow(&self) -> anyhow::Result<Vec<Vec<PathBuf>>>;
+
+    /// Return the `n` largest regular files in the directory tree below `self` with their sizes
+    ///
+    /// Results are sorted by descending size, then by path. Symbolic links are not followed.
+    /// Entries whose metadata cannot be read are skipped and their errors aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection) after the whole tree has been scanned.
+    fn largest_entries_anyhow(&self, n: usize) -> anyhow::Result<Vec<(PathBuf, u64)>>;
+
+    /// Write embedded `assets`, given as `(relative path, contents)` pairs, into the directory
+    /// `self`
+    ///
+    /// Parent directories are created as needed, and files which already have the embedded
+    /// contents are left alone. Asset names must be relative and must not contain `..`. Every
+    /// asset is attempted; failures carry the asset name as context and are aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection). On success, the report lists written and
+    /// unchanged paths in asset order.
+    fn extract_assets_anyhow(&self, assets: &[(&str, &[u8])]) -> anyhow::Result<ExtractReport>;
+
+    /// Remove entries of the directory `self` last modified more than `age` ago
+    ///
+    /// Subdirectories are removed recursively based on their own modification time; symbolic
+    /// links are removed, not followed. If `dry_run` is `true`, nothing is removed. Returns the
+    /// sorted paths which were removed, or would have been removed in a dry run. Entries which
+    /// cannot be inspected or removed are skipped and their errors aggregated into an
+    /// [ErrorCollection](crate::ErrorCollection) after every entry has been processed.
+    fn remove_older_than_anyhow(
+        &self,
+        age: Duration,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<PathBuf>>;
+
+    /// Rotate the file `self`, keeping `keep` older generations
+    ///
+    /// For a path like `app.log`, this removes `app.log.<keep>`, renames each `app.log.N` to
+    /// `app.log.<N+1>`, then renames `app.log` to `app.log.1`. Missing generations are skipped.
+    /// With `keep` of `0`, `app.log` is simply removed.
+    fn rotate_anyhow(&self, keep: usize) -> anyhow::Result<()>;
+
+    /// Rotate the file `self` like [PathAnyhow::rotate_anyhow], gzip-compressing rotated generations
+    ///
+    /// Generations are named `app.log.N.gz`, and `app.log` is compressed into `app.log.1.gz`
+    /// then removed.
+    #[cfg(feature = "flate2")]
+    fn rotate_compressed_anyhow(&self, keep: usize) -> anyhow::Result<()>;
+
     /// Wrap [std::fs::set_permissions], providing the path as error context
     fn set_permissions_anyhow(&self, perm: Permissions) -> anyhow::Result<()>;
 
@@ -89,35 +552,138 @@ pub trait PathAnyhow {
     /// them, and then setting them.
     fn set_readonly_anyhow(&self, readonly: bool) -> anyhow::Result<()>;
 
+    /// Set the modification time of the file, as with [File::set_modified]
+    ///
+    /// The file is opened internally, read-only on Unix so that read-only files and directories
+    /// are supported, and for writing elsewhere. The requested time is included in the error
+    /// context.
+    fn set_modified_anyhow(&self, modified: SystemTime) -> anyhow::Result<()>;
+
+    /// Set the access and modification times of the file, as with [File::set_times], leaving
+    /// those given as `None` unchanged
+    ///
+    /// The file is opened as for [PathAnyhow::set_modified_anyhow]. The requested times are
+    /// included in the error context, e.g. `with access time 1000000000s after the Unix epoch`.
+    fn set_times_anyhow(
+        &self,
+        accessed: Option<SystemTime>,
+        modified: Option<SystemTime>,
+    ) -> anyhow::Result<()>;
+
     /// Wrap [std::fs::write], providing the path as error context
     fn write_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
     where
         C: AsRef<[u8]>;
 
+    /// Write `text` to the file like [PathAnyhow::write_anyhow], converting every line ending to
+    /// `eol`
+    fn write_text_anyhow(&self, text: &str, eol: LineEnding) -> anyhow::Result<()>;
+
+    /// Write `contents` to the file, first copying any existing file to a backup
+    ///
+    /// The backup path appends `suffix` to the file name, e.g. `config.toml.bak` for a suffix of
+    /// `".bak"`, replacing any previous backup. The copy is made under a temporary name and then
+    /// renamed into place, so the backup is never partially written. The error context
+    /// distinguishes a failed backup from a failed write. On success, the backup path is
+    /// returned, or `None` if the file did not exist.
+    fn write_with_backup_anyhow<C>(
+        &self,
+        contents: C,
+        suffix: &str,
+    ) -> anyhow::Result<Option<PathBuf>>
+    where
+        C: AsRef<[u8]>;
+
+    /// Write `contents` to the file, opening it according to `mode`
+    ///
+    /// Unlike [PathAnyhow::write_anyhow], which always truncates, this makes the caller state
+    /// whether the file is expected to exist. Open failures include the mode as context.
+    fn write_mode_anyhow<C>(&self, contents: C, mode: WriteMode) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>;
+
+    /// Create a new file containing `contents`, failing if the file already exists
+    ///
+    /// This is [PathAnyhow::write_mode_anyhow] with [WriteMode::CreateNew], except that an
+    /// existing file produces the context `refusing to overwrite existing file`. The underlying
+    /// [std::io::Error] keeps its [AlreadyExists](std::io::ErrorKind::AlreadyExists) kind.
+    fn write_new_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>;
+
+    /// Replace the file with `contents` atomically, so readers see either the old or the new
+    /// contents but never a partial write
+    ///
+    /// The contents are written and synced to a uniquely named temporary sibling, such as
+    /// `.config.toml.<random>.tmp`, which is then renamed over `self`. An existing file's
+    /// permissions are kept. On failure the temporary file is removed and the error context names
+    /// both paths: `while processing path "<path>": with temporary path "<tmp>"`.
+    fn write_atomic_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>;
+
+    /// Create or truncate the file and write all of `slices` to it with vectored writes
+    ///
+    /// Write errors include the index of the slice being written and the total bytes written so
+    /// far as context.
+    fn write_slices_anyhow(&self, slices: &[IoSlice<'_>]) -> anyhow::Result<()>;
+
     /// Wrap [std::env::set_current_dir], providing the path as error context
     fn set_to_current_dir_anyhow(&self) -> anyhow::Result<()>;
 
+    /// Change the current directory to `self` until the returned guard is dropped
+    ///
+    /// The current directory is process-wide, so on Linux this refuses to run while the process
+    /// has more than one thread; elsewhere the caller must ensure no other thread relies on it.
+    /// Errors name both the previous and the new directory.
+    fn push_dir_anyhow(&self) -> anyhow::Result<CurrentDirGuard>;
+
     // File APIs:
     /// Open a [File] in read-only mode wrapping [File::open]
     fn open_file_anyhow(&self) -> anyhow::Result<File>;
 
     /// Open a [File] in write-only mode wrapping [File::create]
     fn create_file_anyhow(&self) -> anyhow::Result<File>;
+
+    /// Iterate over the contents of the file in chunks of `chunk_size` bytes
+    ///
+    /// This reads large files with bounded memory; read errors include the byte offset. A
+    /// `chunk_size` of zero is an error.
+    fn chunks_anyhow(&self, chunk_size: usize) -> anyhow::Result<Chunks>;
+
+    /// Open a [File] for appending, creating it if needed, as with [WriteMode::Append]
+    fn append_anyhow(&self) -> anyhow::Result<File>;
 }
 
 macro_rules! wrap_method {
     ( $method:ident, $cb:expr, $ret:ty, None: $errordesc:expr ) => {
         fn $method(&self) -> anyhow::Result<$ret> {
             let p = self.as_ref();
-            $cb(p)
-                .ok_or_else(|| anyhow::Error::msg($errordesc))
-                .with_context(|| format!("while processing path {:?}", p.display()))
+            with_path_context($cb(p).ok_or_else(|| anyhow::Error::msg($errordesc)), p)
         }
     };
 
     ( $method:ident, $cb:expr, $ret:ty ) => {
         fn $method(&self) -> anyhow::Result<$ret> {
-            $cb(self).with_context(|| format!("while processing path {:?}", self.display()))
+            with_path_context($cb(self), self)
+        }
+    };
+
+    ( $method:ident, $cb:expr, $ret:ty, Audit: $op:literal ) => {
+        fn $method(&self) -> anyhow::Result<$ret> {
+            crate::audit::audited($op, &[self], || with_path_context($cb(self), self))
+        }
+    };
+
+    ( $method:ident, $cb:expr, Operation: $op:ident, $ret:ty, Audit: $name:literal ) => {
+        fn $method<Q>(&self, to: Q) -> anyhow::Result<$ret>
+        where
+            Q: AsRef<Path>,
+        {
+            let to = to.as_ref();
+            crate::audit::audited($name, &[self, to], || {
+                with_two_path_context($cb(self, to), Operation::$op, self, to)
+            })
         }
     };
 
@@ -127,9 +693,11 @@ macro_rules! wrap_method {
             Q: AsRef<Path>,
         {
             let argref = $arg.as_ref();
-            $cb(self, argref)
-                .with_context(|| format!("with {} {:?}", stringify!($arg), argref.display()))
-                .with_context(|| format!("while processing path {:?}", self.display()))
+            with_path_context(
+                $cb(self, argref)
+                    .with_context(|| value_context(stringify!($arg), render_path(argref))),
+                self,
+            )
         }
     };
 }
@@ -172,6 +740,26 @@ impl PathAnyhow for Path {
         None: "missing expected extension"
     );
 
+    fn file_name_str_anyhow(&self) -> anyhow::Result<&str> {
+        component_str(self, self.file_name_anyhow()?, "file name")
+    }
+
+    fn file_stem_str_anyhow(&self) -> anyhow::Result<&str> {
+        component_str(self, self.file_stem_anyhow()?, "file stem")
+    }
+
+    fn extension_str_anyhow(&self) -> anyhow::Result<&str> {
+        component_str(self, self.extension_anyhow()?, "extension")
+    }
+
+    fn expect_extension_anyhow(&self, ext: &str) -> anyhow::Result<()> {
+        expect_extension(self, ext, false)
+    }
+
+    fn expect_extension_ignore_case_anyhow(&self, ext: &str) -> anyhow::Result<()> {
+        expect_extension(self, ext, true)
+    }
+
     wrap_method!(
         metadata_anyhow,
         |p: &Path| p.metadata().map(|md| Metadata::from((md, p.to_path_buf()))),
@@ -184,24 +772,368 @@ impl PathAnyhow for Path {
             .map(|md| Metadata::from((md, p.to_path_buf()))),
         Metadata
     );
+
+    fn stat_anyhow(&self, follow: FollowSymlinks) -> anyhow::Result<Metadata> {
+        let res = match follow {
+            FollowSymlinks::Yes => self.metadata(),
+            FollowSymlinks::No => self.symlink_metadata(),
+        };
+        with_path_context(
+            res.map(|md| Metadata::from((md, self.to_path_buf())))
+                .with_context(|| value_context("follow symlinks", follow)),
+            self,
+        )
+    }
+
+    fn exists_no_follow_anyhow(&self) -> anyhow::Result<bool> {
+        match self.symlink_metadata() {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => with_path_context(Err(e), self),
+        }
+    }
+
+    fn classify_anyhow(&self) -> anyhow::Result<FileKind> {
+        FileKind::of_path(self)
+    }
+
+    fn ensure_dir_anyhow(&self) -> anyhow::Result<Metadata> {
+        ensure_kind(self, FileKind::Dir)
+    }
+
+    fn ensure_file_anyhow(&self) -> anyhow::Result<Metadata> {
+        ensure_kind(self, FileKind::File)
+    }
+
+    fn expect_contents_anyhow<C>(&self, expected: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::expect::expect_contents(self, expected.as_ref())
+    }
+
+    fn expect_contains_anyhow<C>(&self, needle: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::expect::expect_contains(self, needle.as_ref())
+    }
+
+    #[cfg(feature = "sniff")]
+    fn detect_kind_anyhow(&self) -> anyhow::Result<crate::fs::ContentKind> {
+        use std::io::Read;
+
+        let mut prefix = Vec::with_capacity(crate::fs::SNIFF_LEN);
+        let f = self.open_file_anyhow()?;
+        with_path_context(
+            f.take(crate::fs::SNIFF_LEN as u64).read_to_end(&mut prefix),
+            self,
+        )?;
+        Ok(crate::fs::ContentKind::sniff(&prefix))
+    }
+
+    #[cfg(feature = "sniff")]
+    fn expect_kind_anyhow(&self, expected: crate::fs::ContentKind) -> anyhow::Result<()> {
+        let found = self.detect_kind_anyhow()?;
+        if found == expected {
+            Ok(())
+        } else {
+            with_path_context(
+                Err(anyhow::anyhow!(
+                    "expected {}, but the contents look like {}",
+                    expected,
+                    found
+                )),
+                self,
+            )
+        }
+    }
+
+    fn next_available_name_anyhow(&self, max_attempts: usize) -> anyhow::Result<PathBuf> {
+        self::naming::next_available_name(self, max_attempts)
+    }
+
+    fn unique_path_anyhow(&self, prefix: &str, suffix: &str) -> anyhow::Result<PathBuf> {
+        self::unique::unique_path(self, prefix, suffix)
+    }
+
     wrap_method!(canonicalize_anyhow, Path::canonicalize, PathBuf);
+
+    fn expand_anyhow(&self) -> anyhow::Result<PathBuf> {
+        self::expand::expand(self)
+    }
+
+    fn canonicalize_missing_anyhow(&self) -> anyhow::Result<PathBuf> {
+        self::normalize::canonicalize_missing(self)
+    }
+
+    fn normalize_lexically_anyhow(&self) -> anyhow::Result<PathBuf> {
+        self::normalize::normalize_lexically(self)
+    }
+
+    fn split_prefix_anyhow<P>(&self, base: P) -> anyhow::Result<(PathBuf, PathBuf)>
+    where
+        P: AsRef<Path>,
+    {
+        self::prefix::split_prefix(self, base.as_ref())
+    }
+
+    fn relative_to_anyhow<P>(&self, base: P) -> anyhow::Result<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        self::relative::relative_to(self, base.as_ref())
+    }
+
+    fn join_secure_anyhow<P>(&self, untrusted: P) -> anyhow::Result<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        self::secure::join_secure(self, untrusted.as_ref())
+    }
     wrap_method!(read_link_anyhow, Path::read_link, PathBuf);
     wrap_method!(read_dir_anyhow, ReadDir::from_path, ReadDir);
-    wrap_method!(copy_anyhow, std::fs::copy, AsRefPath: copy_to, u64);
-    wrap_method!(create_dir_anyhow, std::fs::create_dir, ());
-    wrap_method!(create_dir_all_anyhow, std::fs::create_dir_all, ());
-    wrap_method!(hard_link_anyhow, std::fs::hard_link, AsRefPath: link_to, ());
+
+    fn walk_anyhow(&self) -> anyhow::Result<Walk> {
+        Walk::new(self)
+    }
+    wrap_method!(
+        copy_anyhow,
+        std::fs::copy,
+        Operation: Copy,
+        u64,
+        Audit: "copy"
+    );
+
+    fn copy_dir_all_anyhow<P>(&self, to: P, options: CopyOptions) -> anyhow::Result<u64>
+    where
+        P: AsRef<Path>,
+    {
+        self::copydir::copy_dir_all(self, to.as_ref(), options)
+    }
+
+    wrap_method!(create_dir_anyhow, std::fs::create_dir, (), Audit: "create_dir");
+    wrap_method!(
+        create_dir_all_anyhow,
+        std::fs::create_dir_all,
+        (),
+        Audit: "create_dir_all"
+    );
+    wrap_method!(
+        hard_link_anyhow,
+        std::fs::hard_link,
+        Operation: HardLink,
+        (),
+        Audit: "hard_link"
+    );
     wrap_method!(read_anyhow, std::fs::read, Vec<u8>);
     wrap_method!(read_to_string_anyhow, std::fs::read_to_string, String);
-    wrap_method!(remove_dir_anyhow, std::fs::remove_dir, ());
-    wrap_method!(remove_dir_all_anyhow, std::fs::remove_dir_all, ());
-    wrap_method!(remove_file_anyhow, std::fs::remove_file, ());
-    wrap_method!(rename_anyhow, std::fs::rename, AsRefPath: rename_to, ());
+
+    fn read_to_string_limited_anyhow(&self, max_bytes: u64) -> anyhow::Result<String> {
+        self::text::read_to_string_limited(self, max_bytes)
+    }
+
+    fn read_to_string_lossy_anyhow(
+        &self,
+        max_replacements: Option<usize>,
+    ) -> anyhow::Result<(String, Vec<usize>)> {
+        self::text::read_to_string_lossy(self, max_replacements)
+    }
+
+    #[cfg(feature = "hex")]
+    fn read_hex_anyhow(&self) -> anyhow::Result<Vec<u8>> {
+        self::codec::read_hex(self)
+    }
+
+    #[cfg(feature = "hex")]
+    fn write_hex_anyhow(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        self::codec::write_hex(self, bytes)
+    }
+
+    #[cfg(feature = "base64")]
+    fn read_base64_anyhow(&self) -> anyhow::Result<Vec<u8>> {
+        self::codec::read_base64(self)
+    }
+
+    #[cfg(feature = "base64")]
+    fn write_base64_anyhow(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        self::codec::write_base64(self, bytes)
+    }
+
+    #[cfg(feature = "encoding")]
+    fn read_text_anyhow(&self) -> anyhow::Result<String> {
+        self.read_text_encoded_anyhow(encoding_rs::UTF_8)
+    }
+
+    #[cfg(feature = "encoding")]
+    fn read_text_encoded_anyhow(
+        &self,
+        fallback: &'static encoding_rs::Encoding,
+    ) -> anyhow::Result<String> {
+        self::text::read_text(self, fallback)
+    }
+
+    fn read_text_normalized_anyhow(&self) -> anyhow::Result<String> {
+        self.read_to_string_anyhow()
+            .map(|text| crate::fs::normalize_to_lf(&text))
+    }
+
+    wrap_method!(remove_dir_anyhow, std::fs::remove_dir, (), Audit: "remove_dir");
+    wrap_method!(
+        remove_dir_all_anyhow,
+        std::fs::remove_dir_all,
+        (),
+        Audit: "remove_dir_all"
+    );
+    wrap_method!(remove_file_anyhow, std::fs::remove_file, (), Audit: "remove_file");
+    wrap_method!(
+        rename_anyhow,
+        std::fs::rename,
+        Operation: Rename,
+        (),
+        Audit: "rename"
+    );
+
+    fn rename_changing_case_anyhow<P>(&self, to: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self::caserename::rename_changing_case(self, to.as_ref())
+    }
+
+    fn move_anyhow<P>(&self, to: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self::moving::move_path(self, to.as_ref())
+    }
+
+    fn rename_matching_anyhow<F>(&self, rename: F) -> anyhow::Result<usize>
+    where
+        F: FnMut(&OsStr) -> Option<OsString>,
+    {
+        self::rename_matching::rename_matching(self, rename)
+    }
+
+    fn for_each_entry_anyhow<F>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<()>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<()>,
+    {
+        self.collect_dir_anyhow(policy, f)
+    }
+
+    fn map_dir_anyhow<F, T>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<T>,
+    {
+        self.collect_dir_anyhow(policy, f)
+    }
+
+    fn collect_dir_anyhow<F, T, C>(&self, policy: ErrorPolicy, f: F) -> anyhow::Result<C>
+    where
+        F: FnMut(DirEntry) -> anyhow::Result<T>,
+        C: FromIterator<T>,
+    {
+        self::entries::collect_dir(self, policy, f)
+    }
+
+    fn manifest_anyhow(&self) -> anyhow::Result<Manifest> {
+        self::manifest::manifest(self, None)
+    }
+
+    #[cfg(feature = "sha2")]
+    fn manifest_sha256_anyhow(&self) -> anyhow::Result<Manifest> {
+        self::manifest::manifest(self, Some(crate::fs::sha256_hex))
+    }
+
+    fn verify_manifest_anyhow(&self, manifest: &Manifest) -> anyhow::Result<ManifestDiff> {
+        self::manifest::verify_manifest(self, manifest)
+    }
+
+    fn find_broken_links_anyhow(&self) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+        self::links::find_broken_links(self)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn create_dir_with_mode_anyhow(&self, mode: u32) -> anyhow::Result<()> {
+        self::perms::create_dir_with_mode(self, mode)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn create_private_dir_anyhow(&self) -> anyhow::Result<()> {
+        self.create_dir_with_mode_anyhow(0o700)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn set_mode_anyhow(&self, mode: u32) -> anyhow::Result<()> {
+        self::perms::set_mode(self, mode)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn chmod_recursive_anyhow<F>(&self, mode_fn: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&FileKind) -> u32,
+    {
+        self::perms::chmod_recursive(self, mode_fn)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn chown_recursive_anyhow(
+        &self,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        self::perms::chown_recursive(self, uid, gid, dry_run)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn read_secret_anyhow(&self, allowed_mode: u32) -> anyhow::Result<Vec<u8>> {
+        self::perms::read_secret(self, allowed_mode)
+    }
+
+    #[cfg(target_family = "unix")]
+    fn find_world_writable_anyhow(&self) -> anyhow::Result<Vec<(PathBuf, u32)>> {
+        self::perms::find_world_writable(self)
+    }
+
+    fn find_duplicates_anyhow(&self) -> anyhow::Result<Vec<Vec<PathBuf>>> {
+        self::duplicates::find_duplicates(self)
+    }
+
+    fn largest_entries_anyhow(&self, n: usize) -> anyhow::Result<Vec<(PathBuf, u64)>> {
+        self::largest::largest_entries(self, n)
+    }
+
+    fn extract_assets_anyhow(&self, assets: &[(&str, &[u8])]) -> anyhow::Result<ExtractReport> {
+        self::assets::extract_assets(self, assets)
+    }
+
+    fn remove_older_than_anyhow(
+        &self,
+        age: Duration,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        self::cleanup::remove_older_than(self, age, dry_run)
+    }
+
+    fn rotate_anyhow(&self, keep: usize) -> anyhow::Result<()> {
+        self::rotate::rotate(self, keep)
+    }
+
+    #[cfg(feature = "flate2")]
+    fn rotate_compressed_anyhow(&self, keep: usize) -> anyhow::Result<()> {
+        self::rotate::rotate_compressed(self, keep)
+    }
 
     fn set_permissions_anyhow(&self, perms: Permissions) -> anyhow::Result<()> {
-        std::fs::set_permissions(self, perms.clone())
-            .with_context(|| format!("with permissions {:?}", perms))
-            .with_context(|| format!("while processing path {:?}", self.display()))
+        crate::audit::audited("set_permissions", &[self], || {
+            with_path_context(
+                std::fs::set_permissions(self, perms.clone())
+                    .with_context(|| value_context("permissions", format_args!("{:?}", perms))),
+                self,
+            )
+        })
     }
 
     fn set_readonly_anyhow(&self, readonly: bool) -> anyhow::Result<()> {
@@ -210,17 +1142,135 @@ impl PathAnyhow for Path {
         self.set_permissions_anyhow(perms)
     }
 
+    fn set_modified_anyhow(&self, modified: SystemTime) -> anyhow::Result<()> {
+        self::times::set_times(self, None, Some(modified))
+    }
+
+    fn set_times_anyhow(
+        &self,
+        accessed: Option<SystemTime>,
+        modified: Option<SystemTime>,
+    ) -> anyhow::Result<()> {
+        self::times::set_times(self, accessed, modified)
+    }
+
     fn write_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
     where
         C: AsRef<[u8]>,
     {
-        std::fs::write(self, contents)
-            .with_context(|| format!("while writing to {:?}", self.display()))
+        crate::audit::audited("write", &[self], || {
+            with_write_context(std::fs::write(self, contents), self)
+        })
+    }
+
+    fn write_text_anyhow(&self, text: &str, eol: LineEnding) -> anyhow::Result<()> {
+        self.write_anyhow(eol.apply(text))
+    }
+
+    fn write_with_backup_anyhow<C>(
+        &self,
+        contents: C,
+        suffix: &str,
+    ) -> anyhow::Result<Option<PathBuf>>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::write::write_with_backup(self, contents.as_ref(), suffix)
+    }
+
+    fn write_mode_anyhow<C>(&self, contents: C, mode: WriteMode) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::write::write_mode(self, contents.as_ref(), mode)
+    }
+
+    fn write_new_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::write::write_new(self, contents.as_ref())
+    }
+
+    fn write_atomic_anyhow<C>(&self, contents: C) -> anyhow::Result<()>
+    where
+        C: AsRef<[u8]>,
+    {
+        self::write::write_atomic(self, contents.as_ref())
+    }
+
+    fn write_slices_anyhow(&self, slices: &[IoSlice<'_>]) -> anyhow::Result<()> {
+        self::write::write_slices(self, slices)
     }
 
     wrap_method!(set_to_current_dir_anyhow, std::env::set_current_dir, ());
+
+    fn push_dir_anyhow(&self) -> anyhow::Result<CurrentDirGuard> {
+        CurrentDirGuard::push(self, true)
+    }
+
     wrap_method!(open_file_anyhow, File::open, File);
-    wrap_method!(create_file_anyhow, File::create, File);
+    wrap_method!(create_file_anyhow, File::create, File, Audit: "create_file");
+    fn chunks_anyhow(&self, chunk_size: usize) -> anyhow::Result<Chunks> {
+        Chunks::open(self, chunk_size)
+    }
+
+    wrap_method!(
+        append_anyhow,
+        |p| WriteMode::Append.open_options().open(p),
+        File,
+        Audit: "append_file"
+    );
+}
+
+/// Convert the component `what` of `path` to UTF-8, with only the path as context on failure
+fn component_str<'a>(path: &Path, component: &'a OsStr, what: &str) -> anyhow::Result<&'a str> {
+    match component.to_str() {
+        Some(s) => Ok(s),
+        None => with_path_context(
+            Err(anyhow::anyhow!(
+                "{what} {} is not valid UTF8",
+                escape_os(component)
+            )),
+            path,
+        ),
+    }
+}
+
+fn expect_extension(path: &Path, ext: &str, ignore_case: bool) -> anyhow::Result<()> {
+    let actual = path.extension();
+    let matches = actual.is_some_and(|a| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(ext)
+        } else {
+            a == ext
+        }
+    });
+    if matches {
+        return Ok(());
+    }
+    with_path_context(
+        Err(anyhow::anyhow!(
+            "expected extension {}{}, found {}",
+            escape_os(ext),
+            if ignore_case { " ignoring case" } else { "" },
+            actual.map_or("none".to_string(), escape_os),
+        )),
+        path,
+    )
+}
+
+fn ensure_kind(path: &Path, expected: FileKind) -> anyhow::Result<Metadata> {
+    let md = path.metadata_anyhow()?;
+    let found = FileKind::from_non_symlink(md.file_type());
+    if found == expected {
+        Ok(md)
+    } else {
+        with_path_context(
+            Err(anyhow::anyhow!("not a {} (found {})", expected, found)),
+            path,
+        )
+    }
 }
 
 #[cfg(test)]
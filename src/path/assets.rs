@@ -0,0 +1,44 @@
+use crate::context::value_context;
+use crate::fs::ExtractReport;
+use crate::{ErrorCollection, PathAnyhow};
+use anyhow::Context;
+use std::path::{Component, Path};
+
+pub(super) fn extract_assets(
+    dir: &Path,
+    assets: &[(&str, &[u8])],
+) -> anyhow::Result<ExtractReport> {
+    let mut errors = ErrorCollection::new();
+    let mut report = ExtractReport::default();
+    for &(name, contents) in assets {
+        let path = dir.join(name);
+        let res = extract_asset(name, &path, contents)
+            .with_context(|| value_context("asset", format_args!("{:?}", name)));
+        match errors.ok(res) {
+            Some(true) => report.written.push(path),
+            Some(false) => report.unchanged.push(path),
+            None => {}
+        }
+    }
+    errors.into_result(report)
+}
+
+/// Write `contents` to `path` unless it already has them, returning whether it was written
+fn extract_asset(name: &str, path: &Path, contents: &[u8]) -> anyhow::Result<bool> {
+    let rel = Path::new(name);
+    if !rel
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        anyhow::bail!("asset names must be relative paths without \"..\"");
+    }
+
+    if path.exists_no_follow_anyhow()? && path.read_anyhow()? == contents {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        parent.create_dir_all_anyhow()?;
+    }
+    path.write_anyhow(contents)?;
+    Ok(true)
+}
@@ -0,0 +1,74 @@
+use crate::audit::audited;
+use crate::context::{render_path, with_path_context, with_two_path_context, Operation};
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use super::moving::Rename;
+
+pub(super) fn rename_changing_case(from: &Path, to: &Path) -> anyhow::Result<()> {
+    rename_changing_case_with(from, to, |from, to| std::fs::rename(from, to))
+}
+
+/// Rename as [rename_changing_case] does, renaming with `rename` so tests can force failures
+pub(super) fn rename_changing_case_with(
+    from: &Path,
+    to: &Path,
+    rename: Rename,
+) -> anyhow::Result<()> {
+    let rename_anyhow = |from: &Path, to: &Path| {
+        audited("rename", &[from, to], || {
+            with_two_path_context(rename(from, to), Operation::Rename, from, to)
+        })
+    };
+    if !differs_only_in_case(from, to) {
+        return rename_anyhow(from, to);
+    }
+
+    let tmp = case_rename_temp_path(from)?;
+    rename_anyhow(from, &tmp).context("while renaming to temporary path (step 1 of 2)")?;
+    let Err(e) = rename_anyhow(&tmp, to) else {
+        return Ok(());
+    };
+    let e = e.context("while renaming from temporary path (step 2 of 2)");
+    match rename_anyhow(&tmp, from) {
+        Ok(()) => Err(e),
+        Err(restore) => Err(e.context(format!(
+            "the file remains at {}, as renaming it back failed: {:#}",
+            render_path(&tmp),
+            restore
+        ))),
+    }
+}
+
+fn differs_only_in_case(a: &Path, b: &Path) -> bool {
+    if a.parent() != b.parent() {
+        return false;
+    }
+    match (
+        a.file_name().and_then(OsStr::to_str),
+        b.file_name().and_then(OsStr::to_str),
+    ) {
+        (Some(a), Some(b)) => a != b && a.to_lowercase() == b.to_lowercase(),
+        _ => false,
+    }
+}
+
+fn case_rename_temp_path(p: &Path) -> anyhow::Result<PathBuf> {
+    const MAX_ATTEMPTS: usize = 100;
+
+    let name = p.file_name_anyhow()?.to_string_lossy();
+    for n in 0..MAX_ATTEMPTS {
+        let candidate = p.with_file_name(format!(".{name}.case-rename.{n}"));
+        if !candidate.exists_no_follow_anyhow()? {
+            return Ok(candidate);
+        }
+    }
+    with_path_context(
+        Err(anyhow::anyhow!(
+            "no unused temporary name found after {MAX_ATTEMPTS} attempts"
+        )),
+        p,
+    )
+}
@@ -0,0 +1,45 @@
+use crate::{ErrorCollection, PathAnyhow};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub(super) fn remove_older_than(
+    dir: &Path,
+    age: Duration,
+    dry_run: bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let Some(cutoff) = SystemTime::now().checked_sub(age) else {
+        return Ok(vec![]);
+    };
+
+    let mut errors = ErrorCollection::new();
+    let mut removed = vec![];
+    for entry in dir.read_dir_anyhow()? {
+        let Some(entry) = errors.ok(entry) else {
+            continue;
+        };
+        let Some((md, mtime)) = errors.ok(entry.metadata().and_then(|md| {
+            let mtime = md.modified()?;
+            Ok((md, mtime))
+        })) else {
+            continue;
+        };
+        if mtime >= cutoff {
+            continue;
+        }
+
+        let path = entry.path();
+        let res = if dry_run {
+            Ok(())
+        } else if md.is_dir() {
+            path.remove_dir_all_anyhow()
+        } else {
+            path.remove_file_anyhow()
+        };
+        if errors.ok(res).is_some() {
+            removed.push(path);
+        }
+    }
+
+    removed.sort();
+    errors.into_result(removed)
+}
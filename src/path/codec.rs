@@ -0,0 +1,53 @@
+#[cfg(feature = "base64")]
+use base64::Engine;
+
+use crate::context::with_path_context;
+use crate::PathAnyhow;
+use std::path::Path;
+
+#[cfg(feature = "hex")]
+pub(super) fn read_hex(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let text = path.read_to_string_anyhow()?;
+    with_path_context(decode_hex(text.trim_end()), path)
+}
+
+#[cfg(feature = "hex")]
+pub(super) fn write_hex(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut text: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    text.push('\n');
+    path.write_anyhow(text)
+}
+
+/// Decode hex digits, reporting the character offset of the first invalid one
+#[cfg(feature = "hex")]
+fn decode_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits = text
+        .char_indices()
+        .map(|(offset, c)| {
+            c.to_digit(16).map(|d| d as u8).ok_or_else(|| {
+                anyhow::anyhow!("invalid hex digit {:?} at character offset {}", c, offset)
+            })
+        })
+        .collect::<anyhow::Result<Vec<u8>>>()?;
+
+    if digits.len() % 2 != 0 {
+        anyhow::bail!("odd number of hex digits: {}", digits.len());
+    }
+    Ok(digits.chunks(2).map(|p| p[0] << 4 | p[1]).collect())
+}
+
+#[cfg(feature = "base64")]
+pub(super) fn read_base64(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let text = path.read_to_string_anyhow()?;
+    with_path_context(
+        base64::engine::general_purpose::STANDARD.decode(text.trim_end()),
+        path,
+    )
+}
+
+#[cfg(feature = "base64")]
+pub(super) fn write_base64(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut text = base64::engine::general_purpose::STANDARD.encode(bytes);
+    text.push('\n');
+    path.write_anyhow(text)
+}
@@ -0,0 +1,134 @@
+use crate::audit::audited;
+use crate::context::{with_path_context, with_two_path_context, Operation};
+use crate::fs::{CopyOptions, SymlinkPolicy};
+use crate::{ErrorCollection, PathAnyhow};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub(super) fn copy_dir_all(from: &Path, to: &Path, options: CopyOptions) -> anyhow::Result<u64> {
+    check_not_into_itself(from, to)?;
+    let follow = options.symlinks == SymlinkPolicy::Follow;
+    let walk = from.walk_anyhow()?.follow_symlinks(follow);
+    // `to` itself may be a symlink to the chosen destination directory.
+    if !to.is_dir() {
+        to.create_dir_anyhow()?;
+    }
+
+    let mut errors = ErrorCollection::new();
+    let mut copied = 0;
+    // Source directories whose copy could not be created, so their entries are skipped:
+    let mut failed_dirs: Vec<PathBuf> = vec![];
+    for entry in walk {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let src = entry.path();
+        if failed_dirs.iter().any(|d| src.starts_with(d)) {
+            continue;
+        }
+        let Some(rel) = errors.ok(src.strip_prefix_anyhow(from)) else {
+            continue;
+        };
+        let dst = to.join(rel);
+        let Some(ft) = errors.ok(entry.file_type()) else {
+            continue;
+        };
+
+        let res = if ft.is_symlink() {
+            match options.symlinks {
+                SymlinkPolicy::Recreate => copy_link(&src, &dst, options.overwrite),
+                SymlinkPolicy::Skip => Ok(()),
+                SymlinkPolicy::Follow => match src.metadata() {
+                    Ok(md) if md.is_dir() => {
+                        create_dir(&dst).inspect_err(|_| failed_dirs.push(src.clone()))
+                    }
+                    Ok(_) => copy_file(&src, &dst, options.overwrite).map(|n| copied += n),
+                    // Dangling links have nothing to copy.
+                    Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+                    Err(e) => with_path_context(Err(e), &src),
+                },
+            }
+        } else if ft.is_dir() {
+            create_dir(&dst).inspect_err(|_| failed_dirs.push(src.clone()))
+        } else {
+            copy_file(&src, &dst, options.overwrite).map(|n| copied += n)
+        };
+        errors.ok(res);
+    }
+    errors.into_result(copied)
+}
+
+/// Reject a `to` inside `from`, which the lazy walk would otherwise keep descending into
+fn check_not_into_itself(from: &Path, to: &Path) -> anyhow::Result<()> {
+    let canonical_from = from.canonicalize_anyhow()?;
+    if to
+        .canonicalize_missing_anyhow()?
+        .starts_with(&canonical_from)
+    {
+        with_two_path_context(
+            Err(anyhow::anyhow!("cannot copy a directory into itself")),
+            Operation::Copy,
+            from,
+            to,
+        )
+    } else {
+        Ok(())
+    }
+}
+
+/// Create `path` unless it is already a directory
+///
+/// A symlink at `path` is not followed, so it cannot redirect the copy outside the destination
+/// tree; creating the directory fails instead.
+fn create_dir(path: &Path) -> anyhow::Result<()> {
+    match path.symlink_metadata() {
+        Ok(md) if md.is_dir() => Ok(()),
+        _ => path.create_dir_anyhow(),
+    }
+}
+
+fn check_overwrite(src: &Path, dst: &Path, overwrite: bool) -> anyhow::Result<()> {
+    if overwrite || !dst.exists_no_follow_anyhow()? {
+        Ok(())
+    } else {
+        with_two_path_context(
+            Err(anyhow::anyhow!("destination already exists")),
+            Operation::Copy,
+            src,
+            dst,
+        )
+    }
+}
+
+fn copy_file(src: &Path, dst: &Path, overwrite: bool) -> anyhow::Result<u64> {
+    check_overwrite(src, dst, overwrite)?;
+    // Copying onto an existing symlink would write through it, outside the destination tree.
+    if overwrite && dst.exists_no_follow_anyhow()? {
+        dst.remove_file_anyhow()?;
+    }
+    src.copy_anyhow(dst)
+}
+
+pub(super) fn copy_link(src: &Path, dst: &Path, overwrite: bool) -> anyhow::Result<()> {
+    check_overwrite(src, dst, overwrite)?;
+    let target = src.read_link_anyhow()?;
+    audited("symlink", &[dst], || {
+        if overwrite && dst.exists_no_follow_anyhow()? {
+            dst.remove_file_anyhow()?;
+        }
+        with_two_path_context(symlink(&target, dst), Operation::Copy, src, dst)
+    })
+}
+
+#[cfg(target_family = "unix")]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(target_family = "unix"))]
+fn symlink(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "recreating symbolic links is not supported on this platform",
+    ))
+}
@@ -0,0 +1,54 @@
+use crate::context::with_path_context;
+use crate::fs::Walk;
+use crate::{ErrorCollection, PathAnyhow};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hasher;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub(super) fn find_duplicates(root: &Path) -> anyhow::Result<Vec<Vec<PathBuf>>> {
+    let mut errors = ErrorCollection::new();
+
+    let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    for entry in Walk::new(root)? {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let Some(md) = errors.ok(entry.metadata()) else {
+            continue;
+        };
+        if md.is_file() && md.len() > 0 {
+            by_size.entry(md.len()).or_default().push(entry.path());
+        }
+    }
+
+    let mut groups = vec![];
+    for candidates in by_size.into_values().filter(|ps| ps.len() > 1) {
+        let mut by_hash: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for path in candidates {
+            if let Some(hash) = errors.ok(content_hash(&path)) {
+                by_hash.entry(hash).or_default().push(path);
+            }
+        }
+        groups.extend(by_hash.into_values().filter(|ps| ps.len() > 1));
+    }
+
+    for group in groups.iter_mut() {
+        group.sort();
+    }
+    groups.sort();
+    errors.into_result(groups)
+}
+
+fn content_hash(path: &Path) -> anyhow::Result<u64> {
+    let mut f = path.open_file_anyhow()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = with_path_context(f.read(&mut buf), path)?;
+        if n == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.write(&buf[..n]);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::context::{value_context, with_path_context};
+use crate::fs::DirEntry;
+use crate::{ErrorCollection, ErrorPolicy, PathAnyhow};
+use anyhow::Context;
+use std::path::Path;
+
+pub(super) fn collect_dir<F, T, C>(dir: &Path, policy: ErrorPolicy, mut f: F) -> anyhow::Result<C>
+where
+    F: FnMut(DirEntry) -> anyhow::Result<T>,
+    C: FromIterator<T>,
+{
+    let mut errors = ErrorCollection::new();
+    let mut items = vec![];
+    for entry in dir.read_dir_anyhow()? {
+        let res = entry.and_then(|entry| {
+            let name = entry.file_name();
+            with_path_context(
+                f(entry).with_context(|| value_context("entry", format_args!("{:?}", name))),
+                dir,
+            )
+        });
+        items.extend(policy.handle(&mut errors, res)?);
+    }
+    errors.into_result(items.into_iter().collect())
+}
@@ -0,0 +1,107 @@
+use crate::context::with_path_context;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+pub(super) fn expand(path: &Path) -> anyhow::Result<PathBuf> {
+    expand_with(path, |key| std::env::var_os(key))
+}
+
+/// Expand `path` as [expand] does, looking variables up with `lookup` rather than in the
+/// environment
+pub(super) fn expand_with<F>(path: &Path, lookup: F) -> anyhow::Result<PathBuf>
+where
+    F: Fn(&OsStr) -> Option<OsString>,
+{
+    let bytes = path.as_os_str().as_encoded_bytes();
+    with_path_context(expand_bytes(bytes, &lookup), path).map(PathBuf::from)
+}
+
+fn expand_bytes(
+    bytes: &[u8],
+    lookup: &dyn Fn(&OsStr) -> Option<OsString>,
+) -> anyhow::Result<OsString> {
+    let mut out = OsString::new();
+    let mut rest = bytes;
+
+    if let Some(after) = rest.strip_prefix(b"~") {
+        if after.is_empty()
+            || after.starts_with(b"/")
+            || (cfg!(windows) && after.starts_with(b"\\"))
+        {
+            out.push(var(lookup, HOME)?);
+            rest = after;
+        }
+    }
+
+    while let Some(i) = rest.iter().position(|&b| b == b'$' || is_windows_delim(b)) {
+        out.push(os(&rest[..i]));
+        let (name, after) = match (rest[i], rest.get(i + 1)) {
+            (b'$', Some(b'{')) => {
+                let body = &rest[i + 2..];
+                let end = body
+                    .iter()
+                    .position(|&b| b == b'}')
+                    .ok_or_else(|| anyhow::anyhow!("unterminated \"${{\""))?;
+                (&body[..end], &body[end + 1..])
+            }
+            (b'$', _) => {
+                let body = &rest[i + 1..];
+                let end = if body.first().is_some_and(u8::is_ascii_digit) {
+                    0
+                } else {
+                    body.iter()
+                        .take_while(|&&b| b == b'_' || b.is_ascii_alphanumeric())
+                        .count()
+                };
+                (&body[..end], &body[end..])
+            }
+            _ => {
+                let body = &rest[i + 1..];
+                match body.iter().position(|&b| b == b'%') {
+                    Some(end) => (&body[..end], &body[end + 1..]),
+                    None => (&body[..0], body),
+                }
+            }
+        };
+
+        if name.is_empty() {
+            // A lone delimiter is kept literally.
+            out.push(os(&rest[i..i + 1]));
+            rest = &rest[i + 1..];
+        } else {
+            out.push(var(lookup, os(name))?);
+            rest = after;
+        }
+    }
+    out.push(os(rest));
+    Ok(out)
+}
+
+#[cfg(not(windows))]
+const HOME: &str = "HOME";
+
+#[cfg(windows)]
+const HOME: &str = "USERPROFILE";
+
+fn is_windows_delim(b: u8) -> bool {
+    cfg!(windows) && b == b'%'
+}
+
+fn var<K>(lookup: &dyn Fn(&OsStr) -> Option<OsString>, key: K) -> anyhow::Result<OsString>
+where
+    K: AsRef<OsStr>,
+{
+    let key = key.as_ref();
+    lookup(key).ok_or_else(|| {
+        anyhow::anyhow!(
+            "environment variable {:?} is not set",
+            key.to_string_lossy()
+        )
+    })
+}
+
+/// Convert a piece of the path split only at ASCII delimiters back into an [OsStr]
+fn os(bytes: &[u8]) -> &OsStr {
+    // SAFETY: every piece comes from a single `OsStr` and is split only next to ASCII bytes.
+    unsafe { OsStr::from_encoded_bytes_unchecked(bytes) }
+}
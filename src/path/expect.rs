@@ -0,0 +1,67 @@
+use crate::context::with_path_context;
+use crate::PathAnyhow;
+use std::path::Path;
+
+/// The longest excerpt of either side shown in a mismatch
+const EXCERPT_LEN: usize = 40;
+
+pub(super) fn expect_contents(path: &Path, expected: &[u8]) -> anyhow::Result<()> {
+    let actual = path.read_anyhow()?;
+    let offset = actual
+        .iter()
+        .zip(expected)
+        .position(|(a, e)| a != e)
+        .unwrap_or(actual.len().min(expected.len()));
+    if offset == actual.len() && offset == expected.len() {
+        return Ok(());
+    }
+
+    // Both excerpts start at the beginning of the differing line, which the two sides share.
+    let line_start = actual[..offset]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = actual[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+    with_path_context(
+        Err(anyhow::anyhow!(
+            "contents differ at line {line}, byte offset {offset}: expected {}, found {}",
+            excerpt(&expected[line_start..]),
+            excerpt(&actual[line_start..]),
+        )),
+        path,
+    )
+}
+
+pub(super) fn expect_contains(path: &Path, needle: &[u8]) -> anyhow::Result<()> {
+    let actual = path.read_anyhow()?;
+    if needle.is_empty() || actual.windows(needle.len()).any(|w| w == needle) {
+        Ok(())
+    } else {
+        with_path_context(
+            Err(anyhow::anyhow!(
+                "contents do not contain {} in {} bytes",
+                excerpt(needle),
+                actual.len()
+            )),
+            path,
+        )
+    }
+}
+
+/// Quote the first line of `bytes`, truncated to [EXCERPT_LEN] bytes
+fn excerpt(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "end of file".to_string();
+    }
+    let line = bytes
+        .split_inclusive(|&b| b == b'\n')
+        .next()
+        .unwrap_or(bytes);
+    let shown = &line[..line.len().min(EXCERPT_LEN)];
+    let ellipsis = if shown.len() < bytes.len() && !shown.ends_with(b"\n") {
+        "…"
+    } else {
+        ""
+    };
+    format!("{:?}{ellipsis}", String::from_utf8_lossy(shown))
+}
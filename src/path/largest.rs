@@ -0,0 +1,34 @@
+use crate::fs::Walk;
+use crate::ErrorCollection;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+
+pub(super) fn largest_entries(root: &Path, n: usize) -> anyhow::Result<Vec<(PathBuf, u64)>> {
+    let mut errors = ErrorCollection::new();
+
+    // A min-heap of the largest entries seen so far, bounded to `n` entries; the preallocation is
+    // capped so a huge `n`, such as `usize::MAX` for all entries, neither overflows nor allocates:
+    let mut heap = BinaryHeap::with_capacity(n.saturating_add(1).min(1024));
+    for entry in Walk::new(root)? {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let Some(md) = errors.ok(entry.metadata()) else {
+            continue;
+        };
+        if md.is_file() {
+            heap.push(Reverse((md.len(), Reverse(entry.path()))));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+    }
+
+    let largest = heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size, Reverse(path)))| (path, size))
+        .collect();
+    errors.into_result(largest)
+}
@@ -0,0 +1,22 @@
+use crate::fs::{FileKind, Walk};
+use crate::{ErrorCollection, PathAnyhow};
+use std::path::{Path, PathBuf};
+
+pub(super) fn find_broken_links(root: &Path) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut errors = ErrorCollection::new();
+    let mut broken = vec![];
+    for entry in Walk::new(root)? {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let path = entry.path();
+        if errors.ok(path.classify_anyhow()) == Some(FileKind::Symlink(None)) {
+            if let Some(target) = errors.ok(path.read_link_anyhow()) {
+                broken.push((path, target));
+            }
+        }
+    }
+
+    broken.sort();
+    errors.into_result(broken)
+}
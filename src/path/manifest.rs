@@ -0,0 +1,103 @@
+use crate::fs::{Manifest, ManifestDiff, ManifestEntry};
+use crate::{ErrorCollection, PathAnyhow};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Computes the digest stored in [ManifestEntry::sha256]
+pub(super) type Digest = fn(&Path) -> anyhow::Result<String>;
+
+pub(super) fn manifest(root: &Path, digest: Option<Digest>) -> anyhow::Result<Manifest> {
+    let mut errors = ErrorCollection::new();
+    let entries = collect(root, digest, &mut errors)?;
+    errors.into_result(Manifest { entries })
+}
+
+pub(super) fn verify_manifest(root: &Path, manifest: &Manifest) -> anyhow::Result<ManifestDiff> {
+    #[cfg(not(feature = "sha2"))]
+    if manifest.entries.iter().any(|e| e.sha256.is_some()) {
+        // Comparing sizes alone would pass files modified in place.
+        return crate::context::with_path_context(
+            Err(anyhow::anyhow!(
+                "manifest records sha256 digests but the `sha2` feature is disabled"
+            )),
+            root,
+        );
+    }
+
+    let mut errors = ErrorCollection::new();
+    let mut actual: BTreeMap<_, _> = collect(root, None, &mut errors)?
+        .into_iter()
+        .map(|e| (e.path, e.size))
+        .collect();
+
+    let mut diff = ManifestDiff::default();
+    for expected in &manifest.entries {
+        let Some(size) = actual.remove(&expected.path) else {
+            diff.missing.push(expected.path.clone());
+            continue;
+        };
+        let matches = size == expected.size
+            && match errors.ok(digest_matches(&root.join(&expected.path), expected)) {
+                Some(matches) => matches,
+                None => continue,
+            };
+        if !matches {
+            diff.modified.push(expected.path.clone());
+        }
+    }
+    diff.extra = actual.into_keys().collect();
+    diff.missing.sort();
+    diff.modified.sort();
+    errors.into_result(diff)
+}
+
+/// Compare the digest of `path` with the one recorded in `expected`, if any
+#[cfg(feature = "sha2")]
+fn digest_matches(path: &Path, expected: &ManifestEntry) -> anyhow::Result<bool> {
+    match &expected.sha256 {
+        Some(digest) => Ok(crate::fs::sha256_hex(path)?.eq_ignore_ascii_case(digest)),
+        None => Ok(true),
+    }
+}
+
+/// Without the `sha2` feature, [verify_manifest] rejects manifests recording digests up front
+#[cfg(not(feature = "sha2"))]
+fn digest_matches(_path: &Path, _expected: &ManifestEntry) -> anyhow::Result<bool> {
+    Ok(true)
+}
+
+/// Collect the sorted entries below `root`, adding per-entry errors to `errors`
+fn collect(
+    root: &Path,
+    digest: Option<Digest>,
+    errors: &mut ErrorCollection,
+) -> anyhow::Result<Vec<ManifestEntry>> {
+    let mut entries = vec![];
+    for entry in root.walk_anyhow()? {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let path = entry.path();
+        let Some(md) = errors.ok(entry.metadata()) else {
+            continue;
+        };
+        if !md.is_file() {
+            continue;
+        }
+        let Some(rel) = errors.ok(path.strip_prefix_anyhow(root)) else {
+            continue;
+        };
+        let sha256 = match digest.map(|d| errors.ok(d(&path))) {
+            Some(None) => continue,
+            Some(Some(hex)) => Some(hex),
+            None => None,
+        };
+        entries.push(ManifestEntry {
+            path: rel.to_path_buf(),
+            size: md.len(),
+            sha256,
+        });
+    }
+    entries.sort();
+    Ok(entries)
+}
@@ -0,0 +1,55 @@
+use crate::audit::audited;
+use crate::context::{value_context, with_two_path_context, Operation};
+use crate::fs::CopyOptions;
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::path::Path;
+
+/// Renames `from` to `to`, as [std::fs::rename] does
+pub(super) type Rename = fn(&Path, &Path) -> std::io::Result<()>;
+
+pub(super) fn move_path(from: &Path, to: &Path) -> anyhow::Result<()> {
+    move_with(from, to, |from, to| std::fs::rename(from, to))
+}
+
+/// Move as [move_path] does, renaming with `rename` so tests can force the copy fallback
+pub(super) fn move_with(from: &Path, to: &Path, rename: Rename) -> anyhow::Result<()> {
+    audited("move", &[from, to], || {
+        with_two_path_context(move_inner(from, to, rename), Operation::Move, from, to)
+    })
+}
+
+fn move_inner(from: &Path, to: &Path, rename: Rename) -> anyhow::Result<()> {
+    match rename(from, to) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+        Err(e) => return Err(e).context(value_context("phase", "rename")),
+    }
+
+    let existed = to.exists_no_follow_anyhow()?;
+    let ft = from.symlink_metadata_anyhow()?.file_type();
+    let copied = if ft.is_dir() {
+        from.copy_dir_all_anyhow(to, CopyOptions::default())
+            .map(drop)
+    } else if ft.is_symlink() {
+        super::copydir::copy_link(from, to, true)
+    } else {
+        from.copy_anyhow(to).map(drop)
+    };
+    if copied.is_err() && !existed {
+        // Best effort: leave no partial copy behind, keeping `from` as the only copy.
+        let _ = if ft.is_dir() {
+            std::fs::remove_dir_all(to)
+        } else {
+            std::fs::remove_file(to)
+        };
+    }
+    copied.context(value_context("phase", "copy"))?;
+
+    let removed = if ft.is_dir() {
+        from.remove_dir_all_anyhow()
+    } else {
+        from.remove_file_anyhow()
+    };
+    removed.context(value_context("phase", "cleanup"))
+}
@@ -0,0 +1,35 @@
+use crate::context::{render_path, with_path_context};
+use crate::{escape_os, PathAnyhow};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+pub(super) fn next_available_name(path: &Path, max_attempts: usize) -> anyhow::Result<PathBuf> {
+    if !path.exists_no_follow_anyhow()? {
+        return Ok(path.to_path_buf());
+    }
+
+    let stem = path.file_stem_anyhow()?;
+    let ext = path.extension();
+    for n in 1..=max_attempts {
+        let mut name = OsString::from(stem);
+        name.push(format!(" ({n})"));
+        if let Some(ext) = ext {
+            name.push(".");
+            name.push(ext);
+        }
+        let candidate = path.with_file_name(name);
+        if !candidate.exists_no_follow_anyhow()? {
+            return Ok(candidate);
+        }
+    }
+
+    // Only add the path context here: the errors above already carry it.
+    with_path_context(
+        Err(anyhow::anyhow!(
+            "no available name for {} in directory {} after {max_attempts} attempts",
+            escape_os(stem),
+            render_path(path.parent().unwrap_or(Path::new(""))),
+        )),
+        path,
+    )
+}
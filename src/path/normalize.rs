@@ -0,0 +1,71 @@
+use crate::context::{render_path, value_context, with_path_context};
+use anyhow::Context;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+pub(super) fn normalize_lexically(path: &Path) -> anyhow::Result<PathBuf> {
+    with_path_context(normalize_components(path), path)
+}
+
+pub(super) fn canonicalize_missing(path: &Path) -> anyhow::Result<PathBuf> {
+    for ancestor in path.ancestors() {
+        let existing = if ancestor.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            ancestor
+        };
+        let mut canonical = match std::fs::canonicalize(existing) {
+            Ok(canonical) => canonical,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                return with_path_context(
+                    Err(e).with_context(|| value_context("ancestor", render_path(existing))),
+                    path,
+                )
+            }
+        };
+
+        // `ancestor` is a prefix of `path` by construction.
+        let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+        for component in rest.components() {
+            match component {
+                Component::ParentDir => {
+                    canonical.pop();
+                }
+                Component::Normal(name) => canonical.push(name),
+                _ => {}
+            }
+        }
+        return Ok(canonical);
+    }
+    with_path_context(Err(anyhow::anyhow!("no existing ancestor")), path)
+}
+
+fn normalize_components(path: &Path) -> anyhow::Result<PathBuf> {
+    let mut normal = PathBuf::new();
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => normal.push(component),
+            Component::Normal(name) => {
+                normal.push(name);
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => {
+                normal.pop();
+                depth -= 1;
+            }
+            Component::ParentDir if path.has_root() => {
+                anyhow::bail!("component \"..\" climbs above the root")
+            }
+            Component::ParentDir => {
+                anyhow::bail!("component \"..\" climbs above the start of the relative path")
+            }
+        }
+    }
+    if normal.as_os_str().is_empty() {
+        normal.push(".");
+    }
+    Ok(normal)
+}
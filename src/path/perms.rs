@@ -0,0 +1,149 @@
+use crate::audit::audited;
+use crate::context::{value_context, with_path_context};
+use crate::fs::{FileKind, Walk};
+use crate::{ErrorCollection, PathAnyhow};
+use anyhow::Context;
+use std::io::Read;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+const WORLD_WRITABLE: u32 = 0o002;
+const SETUID: u32 = 0o4000;
+
+pub(super) fn find_world_writable(root: &Path) -> anyhow::Result<Vec<(PathBuf, u32)>> {
+    let mut errors = ErrorCollection::new();
+    let mut found = vec![];
+    for entry in Walk::new(root)? {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let Some(md) = errors.ok(entry.metadata()) else {
+            continue;
+        };
+        let mode = md.permissions().mode();
+        if !md.is_symlink() && mode & (WORLD_WRITABLE | SETUID) != 0 {
+            found.push((entry.path(), mode & 0o7777));
+        }
+    }
+
+    found.sort();
+    errors.into_result(found)
+}
+
+pub(super) fn chmod_recursive<F>(root: &Path, mut mode_fn: F) -> anyhow::Result<()>
+where
+    F: FnMut(&FileKind) -> u32,
+{
+    let mut errors = ErrorCollection::new();
+    let root_kind = FileKind::from_non_symlink(root.symlink_metadata_anyhow()?.file_type());
+    if root_kind != FileKind::Dir {
+        return set_mode(root, mode_fn(&root_kind));
+    }
+
+    // Walk the tree before changing the root, in case the new mode denies reading it.
+    let walk = Walk::new(root)?;
+    errors.ok(set_mode(root, mode_fn(&root_kind)));
+    for entry in walk {
+        let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) else {
+            continue;
+        };
+        let Some(ft) = errors.ok(entry.file_type()) else {
+            continue;
+        };
+        // Changing a link's mode would change its target, which may be outside the tree.
+        if !ft.is_symlink() {
+            errors.ok(set_mode(
+                &entry.path(),
+                mode_fn(&FileKind::from_non_symlink(ft)),
+            ));
+        }
+    }
+    errors.into_result(())
+}
+
+pub(super) fn chown_recursive(
+    root: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    dry_run: bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut errors = ErrorCollection::new();
+    let mut changed = vec![];
+    let mut visit = |path: PathBuf| -> anyhow::Result<()> {
+        let md = path.symlink_metadata_anyhow()?;
+        if uid.is_some_and(|u| u != md.uid()) || gid.is_some_and(|g| g != md.gid()) {
+            if !dry_run {
+                lchown(&path, uid, gid)?;
+            }
+            changed.push(path);
+        }
+        Ok(())
+    };
+
+    let walk = if root.symlink_metadata_anyhow()?.is_dir() {
+        Some(Walk::new(root)?)
+    } else {
+        None
+    };
+    errors.ok(visit(root.to_path_buf()));
+    for entry in walk.into_iter().flatten() {
+        if let Some(entry) = errors.ok(crate::cancel::unless_cancelled(entry)?) {
+            errors.ok(visit(entry.path()));
+        }
+    }
+    changed.sort();
+    errors.into_result(changed)
+}
+
+fn lchown(path: &Path, uid: Option<u32>, gid: Option<u32>) -> anyhow::Result<()> {
+    let id = |id: Option<u32>| id.map(|id| id.to_string()).unwrap_or_default();
+    audited("chown", &[path], || {
+        with_path_context(
+            std::os::unix::fs::lchown(path, uid, gid)
+                .with_context(|| value_context("owner", format_args!("{}:{}", id(uid), id(gid)))),
+            path,
+        )
+    })
+}
+
+pub(super) fn read_secret(path: &Path, allowed_mode: u32) -> anyhow::Result<Vec<u8>> {
+    // Check the opened file rather than the path, so it cannot be swapped after the check.
+    let mut f = path.open_file_anyhow()?;
+    let mode = with_path_context(f.metadata(), path)?.permissions().mode() & 0o7777;
+    if mode & !allowed_mode != 0 {
+        return with_path_context(
+            Err(anyhow::anyhow!(
+                "permissions {:#o} are too open; at most {:#o} is allowed",
+                mode,
+                allowed_mode
+            )),
+            path,
+        );
+    }
+
+    let mut contents = vec![];
+    with_path_context(f.read_to_end(&mut contents), path)?;
+    Ok(contents)
+}
+
+pub(super) fn create_dir_with_mode(path: &Path, mode: u32) -> anyhow::Result<()> {
+    audited("create_dir", &[path], || {
+        with_path_context(
+            std::fs::DirBuilder::new()
+                .mode(mode)
+                .create(path)
+                .with_context(|| value_context("mode", format_args!("{:#o}", mode))),
+            path,
+        )
+    })
+}
+
+pub(super) fn set_mode(path: &Path, mode: u32) -> anyhow::Result<()> {
+    audited("set_permissions", &[path], || {
+        with_path_context(
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .with_context(|| value_context("mode", format_args!("{:#o}", mode))),
+            path,
+        )
+    })
+}
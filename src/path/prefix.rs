@@ -0,0 +1,50 @@
+use crate::context::{render_path, value_context, with_path_context};
+use crate::{escape_os, PathAnyhow};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+pub(super) fn split_prefix(path: &Path, base: &Path) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let canon = path.canonicalize_anyhow()?;
+    let canon_base = base.canonicalize_anyhow()?;
+
+    let mut common = PathBuf::new();
+    let mut rest = canon.components();
+    for base_comp in canon_base.components() {
+        match rest.next() {
+            Some(comp) if comp == base_comp => common.push(comp),
+            Some(comp) => {
+                return diverged(
+                    path,
+                    base,
+                    format!(
+                        "canonical path {} diverges from canonical prefix {} after {}: {} does not match {}",
+                        render_path(&canon),
+                        render_path(&canon_base),
+                        render_path(&common),
+                        escape_os(comp),
+                        escape_os(base_comp),
+                    ),
+                );
+            }
+            None => {
+                return diverged(
+                    path,
+                    base,
+                    format!(
+                        "canonical path {} ends before canonical prefix {}",
+                        render_path(&canon),
+                        render_path(&canon_base),
+                    ),
+                );
+            }
+        }
+    }
+    Ok((canon_base, rest.as_path().to_path_buf()))
+}
+
+fn diverged<T>(path: &Path, base: &Path, msg: String) -> anyhow::Result<T> {
+    with_path_context(
+        Err(anyhow::Error::msg(msg)).context(value_context("prefix", render_path(base))),
+        path,
+    )
+}
@@ -0,0 +1,59 @@
+use crate::context::{render_path, value_context, with_path_context};
+use crate::escape_os;
+use anyhow::Context;
+use std::path::{Component, Path, PathBuf};
+
+pub(super) fn relative_to(path: &Path, base: &Path) -> anyhow::Result<PathBuf> {
+    with_path_context(
+        relative_components(path, base).with_context(|| value_context("base", render_path(base))),
+        path,
+    )
+}
+
+fn relative_components(path: &Path, base: &Path) -> anyhow::Result<PathBuf> {
+    for p in [path, base] {
+        if !p.is_absolute() {
+            anyhow::bail!("path {} is not absolute", render_path(p));
+        }
+    }
+
+    let mut comps = path
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .peekable();
+    let mut base_comps = base
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .peekable();
+    if let (Some(Component::Prefix(a)), Some(Component::Prefix(b))) =
+        (comps.peek(), base_comps.peek())
+    {
+        if a != b {
+            anyhow::bail!(
+                "no relative path between prefixes {} and {}",
+                escape_os(a.as_os_str()),
+                escape_os(b.as_os_str())
+            );
+        }
+    }
+    while let (Some(a), Some(b)) = (comps.peek(), base_comps.peek()) {
+        if a != b {
+            break;
+        }
+        comps.next();
+        base_comps.next();
+    }
+
+    let mut rel = PathBuf::new();
+    for comp in base_comps {
+        if comp == Component::ParentDir {
+            anyhow::bail!("base has a \"..\" component after the common ancestor");
+        }
+        rel.push("..");
+    }
+    rel.extend(comps);
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+    Ok(rel)
+}
@@ -0,0 +1,73 @@
+use crate::context::with_path_context;
+use crate::{ErrorCollection, PathAnyhow};
+use anyhow::{anyhow, Context};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path};
+
+pub(super) fn rename_matching<F>(dir: &Path, mut rename: F) -> anyhow::Result<usize>
+where
+    F: FnMut(&OsStr) -> Option<OsString>,
+{
+    let mut names = BTreeSet::new();
+    for entry in dir.read_dir_anyhow()? {
+        names.insert(entry?.file_name());
+    }
+
+    let plan: Vec<(&OsString, OsString)> = names
+        .iter()
+        .filter_map(|old| rename(old).map(|new| (old, new)))
+        .filter(|(old, new)| old != &new)
+        .collect();
+
+    let mut collisions = ErrorCollection::new();
+    let mut targets: HashMap<&OsStr, &OsStr> = HashMap::new();
+    for (old, new) in &plan {
+        if !is_plain_file_name(new) {
+            collisions.push(anyhow!(
+                "entry {:?} would be renamed to invalid file name {:?}",
+                old,
+                new
+            ));
+        } else if names.contains(new) {
+            collisions.push(anyhow!(
+                "entry {:?} would be renamed to existing entry {:?}",
+                old,
+                new
+            ));
+        } else if let Some(prev) = targets.insert(new, old) {
+            collisions.push(anyhow!(
+                "entries {:?} and {:?} would both be renamed to {:?}",
+                prev,
+                old,
+                new
+            ));
+        }
+    }
+    with_path_context(
+        collisions
+            .into_result(())
+            .context("rename collisions detected; no entries were renamed"),
+        dir,
+    )?;
+
+    let mut errors = ErrorCollection::new();
+    let mut renamed = 0;
+    for (old, new) in &plan {
+        if errors
+            .ok(dir.join(old).rename_anyhow(dir.join(new)))
+            .is_some()
+        {
+            renamed += 1;
+        }
+    }
+    errors.into_result(renamed)
+}
+
+fn is_plain_file_name(name: &OsStr) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(n)), None) if n == name
+    )
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "flate2")]
+use crate::context::{with_two_path_context, Operation};
+use crate::PathAnyhow;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+pub(super) fn rotate(path: &Path, keep: usize) -> anyhow::Result<()> {
+    rotate_with(path, keep, "", |from, to| from.rename_anyhow(to))
+}
+
+#[cfg(feature = "flate2")]
+pub(super) fn rotate_compressed(path: &Path, keep: usize) -> anyhow::Result<()> {
+    rotate_with(path, keep, ".gz", compress)
+}
+
+/// Shift `path.N{suffix}` generations up by one, then move `path` into generation 1 with `first`
+fn rotate_with<F>(path: &Path, keep: usize, suffix: &str, first: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&Path, &Path) -> anyhow::Result<()>,
+{
+    if keep == 0 {
+        return if path.exists_no_follow_anyhow()? {
+            path.remove_file_anyhow()
+        } else {
+            Ok(())
+        };
+    }
+
+    let oldest = generation(path, keep, suffix);
+    if oldest.exists_no_follow_anyhow()? {
+        oldest.remove_file_anyhow()?;
+    }
+    for n in (1..keep).rev() {
+        let from = generation(path, n, suffix);
+        if from.exists_no_follow_anyhow()? {
+            from.rename_anyhow(generation(path, n + 1, suffix))?;
+        }
+    }
+    if path.exists_no_follow_anyhow()? {
+        first(path, &generation(path, 1, suffix))?;
+    }
+    Ok(())
+}
+
+fn generation(path: &Path, n: usize, suffix: &str) -> PathBuf {
+    let mut s = OsString::from(path);
+    s.push(format!(".{n}{suffix}"));
+    PathBuf::from(s)
+}
+
+#[cfg(feature = "flate2")]
+fn compress(from: &Path, to: &Path) -> anyhow::Result<()> {
+    let mut input = from.open_file_anyhow()?;
+    let mut encoder =
+        flate2::write::GzEncoder::new(to.create_file_anyhow()?, flate2::Compression::default());
+    with_two_path_context(
+        std::io::copy(&mut input, &mut encoder).and_then(|_| encoder.finish()),
+        Operation::Compress,
+        from,
+        to,
+    )?;
+    from.remove_file_anyhow()
+}
@@ -0,0 +1,42 @@
+use crate::context::{value_context, with_path_context};
+use crate::escape_os;
+use anyhow::Context;
+use std::path::{Component, Path, PathBuf};
+
+pub(super) fn join_secure(base: &Path, untrusted: &Path) -> anyhow::Result<PathBuf> {
+    with_path_context(
+        join_components(base, untrusted)
+            .with_context(|| value_context("untrusted path", escape_os(untrusted))),
+        base,
+    )
+}
+
+/// Join `untrusted` onto `base` lexically, without context
+pub(crate) fn join_components(base: &Path, untrusted: &Path) -> anyhow::Result<PathBuf> {
+    let mut joined = base.to_path_buf();
+    let mut depth = 0usize;
+    for component in untrusted.components() {
+        match component {
+            Component::Normal(name) => {
+                joined.push(name);
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => {
+                joined.pop();
+                depth -= 1;
+            }
+            Component::ParentDir => {
+                anyhow::bail!("component \"..\" escapes the base directory")
+            }
+            Component::RootDir => anyhow::bail!(
+                "absolute path component {} is not allowed",
+                escape_os(component)
+            ),
+            Component::Prefix(_) => {
+                anyhow::bail!("prefix component {} is not allowed", escape_os(component))
+            }
+        }
+    }
+    Ok(joined)
+}
@@ -104,6 +104,181 @@ fn extension(input: &str) -> Result<&str, String> {
     )
 }
 
+#[test_case("/foo/bar.tar.gz" => Ok(("bar.tar.gz", "bar.tar", "gz")); "ok")]
+#[test_case(
+    "/foo/.."
+    => err_str(r#"while processing path "/foo/..": missing expected filename"#)
+    ; "missing"
+)]
+fn component_strs(input: &str) -> Result<(&str, &str, &str), String> {
+    let path = Path::new(input);
+    stringify_error((|| {
+        Ok((
+            path.file_name_str_anyhow()?,
+            path.file_stem_str_anyhow()?,
+            path.extension_str_anyhow()?,
+        ))
+    })())
+}
+
+#[test_case("a/b.json", false => Ok(()); "ok")]
+#[test_case(
+    "a/b.JSON",
+    false
+    => err_str(r#"while processing path "a/b.JSON": expected extension "json", found "JSON""#)
+    ; "case differs"
+)]
+#[test_case("a/b.JSON", true => Ok(()); "ignoring case")]
+#[test_case(
+    "a/b.txt",
+    true
+    => err_str(r#"while processing path "a/b.txt": expected extension "json" ignoring case, found "txt""#)
+    ; "differs ignoring case"
+)]
+#[test_case(
+    "a/b",
+    false
+    => err_str(r#"while processing path "a/b": expected extension "json", found none"#)
+    ; "missing"
+)]
+fn expect_extension(input: &str, ignore_case: bool) -> Result<(), String> {
+    let path = Path::new(input);
+    stringify_error(if ignore_case {
+        path.expect_extension_ignore_case_anyhow("json")
+    } else {
+        path.expect_extension_anyhow("json")
+    })
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn extension_str_invalid_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(OsStr::from_bytes(b"/foo/bar.\xff"));
+    assert_eq!(
+        stringify_error(path.extension_str_anyhow()),
+        err_str(r#"while processing path "/foo/bar.\xFF": extension "\xFF" is not valid UTF8"#)
+    );
+}
+
+#[test]
+fn canonicalize_missing() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let canonical = dir.path().canonicalize_anyhow()?;
+    dir.path().join("sub").create_dir_anyhow()?;
+
+    assert_eq!(
+        dir.path()
+            .join("sub/new/./file")
+            .canonicalize_missing_anyhow()?,
+        canonical.join("sub/new/file")
+    );
+    assert_eq!(
+        dir.path()
+            .join("new/../../x")
+            .canonicalize_missing_anyhow()?,
+        canonical.parent().unwrap().join("x")
+    );
+    assert_eq!(dir.path().canonicalize_missing_anyhow()?, canonical);
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn canonicalize_missing_err() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let file = dir.path().join("file");
+    file.write_anyhow("")?;
+
+    let err = file
+        .join("new")
+        .canonicalize_missing_anyhow()
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR"),
+        r#"while processing path "DIR/file/new": with ancestor "DIR/file/new": Not a directory (os error 20)"#
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case("~/cfg" => Ok("/home/me/cfg".into()); "tilde")]
+#[test_case("~other/cfg" => Ok("~other/cfg".into()); "tilde user")]
+#[test_case("$APP_DIR/${APP_NAME}.toml" => Ok("/opt/app/app.toml".into()); "vars")]
+#[test_case("cost$/100%" => Ok("cost$/100%".into()); "literal")]
+#[test_case(
+    "$APP_DIR/$APP_UNSET/x"
+    => err_str(r#"while processing path "$APP_DIR/$APP_UNSET/x": environment variable "APP_UNSET" is not set"#)
+    ; "unset"
+)]
+#[test_case(
+    "${APP_DIR"
+    => err_str(r#"while processing path "${APP_DIR": unterminated "${""#)
+    ; "unterminated"
+)]
+fn expand(input: &str) -> Result<std::path::PathBuf, String> {
+    let vars = [
+        ("HOME", "/home/me"),
+        ("APP_DIR", "/opt/app"),
+        ("APP_NAME", "app"),
+    ];
+    stringify_error(super::expand::expand_with(Path::new(input), |key| {
+        vars.iter().find(|(k, _)| key == *k).map(|(_, v)| v.into())
+    }))
+}
+
+#[test_case("/a/./b/../c/" => Ok("/a/c".into()); "absolute")]
+#[test_case("a/b/../.." => Ok(".".into()); "back to start")]
+#[test_case(
+    "/a/../.."
+    => err_str(r#"while processing path "/a/../..": component ".." climbs above the root"#)
+    ; "above root"
+)]
+#[test_case(
+    "../a"
+    => err_str(r#"while processing path "../a": component ".." climbs above the start of the relative path"#)
+    ; "above start"
+)]
+fn normalize_lexically(input: &str) -> Result<std::path::PathBuf, String> {
+    stringify_error(Path::new(input).normalize_lexically_anyhow())
+}
+
+#[test_case("/a/b/c", "/a/d" => Ok("../b/c".into()); "sibling")]
+#[test_case("/a/b", "/a/b" => Ok(".".into()); "equal")]
+#[test_case("/a/b/c", "/a" => Ok("b/c".into()); "below")]
+#[test_case("/a", "/a/b/c" => Ok("../..".into()); "above")]
+#[test_case(
+    "a/b", "/a"
+    => err_str(r#"while processing path "a/b": with base "/a": path "a/b" is not absolute"#)
+    ; "relative"
+)]
+#[test_case(
+    "/a/b", "/c/../d"
+    => err_str(r#"while processing path "/a/b": with base "/c/../d": base has a ".." component after the common ancestor"#)
+    ; "base parent"
+)]
+fn relative_to(path: &str, base: &str) -> Result<std::path::PathBuf, String> {
+    stringify_error(Path::new(path).relative_to_anyhow(base))
+}
+
+#[test_case("a/./b" => Ok("/base/a/b".into()); "nested")]
+#[test_case("a/../b" => Ok("/base/b".into()); "inner parent")]
+#[test_case(
+    "a/../../etc/passwd"
+    => err_str(r#"while processing path "/base": with untrusted path "a/../../etc/passwd": component ".." escapes the base directory"#)
+    ; "escape"
+)]
+#[test_case(
+    "/etc/passwd"
+    => err_str(r#"while processing path "/base": with untrusted path "/etc/passwd": absolute path component "/" is not allowed"#)
+    ; "absolute"
+)]
+fn join_secure(untrusted: &str) -> Result<std::path::PathBuf, String> {
+    stringify_error(Path::new("/base").join_secure_anyhow(untrusted))
+}
+
 #[test_case("/" => Ok(()); "ok root")]
 #[test_case(
     "/this/path/should/not/exist"
@@ -177,25 +352,51 @@ fn read_dir(input: &str) -> Result<(), String> {
     stringify_error(Path::new(input).read_dir_anyhow().map(|_| ()))
 }
 
-#[test_case(
-    "/this/path/should/not/exist",
-    Path::new,
-    "/this/path/also/should/not/exist",
-    |p| format!(
-        "while processing path {:?}: with copy_to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
-        p.display(),
-    );
-    "err non-existing to non-existing"
+#[cfg_attr(
+    not(feature = "compat-messages"),
+    test_case(
+        "/this/path/should/not/exist",
+        Path::new,
+        "/this/path/also/should/not/exist",
+        |p| format!(
+            "while copying path {:?} to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
+            p.display(),
+        );
+        "err non-existing to non-existing"
+    ),
+    test_case(
+        tempfile::NamedTempFile::new().unwrap(),
+        |nft| nft.path(),
+        "/this/path/also/should/not/exist",
+        |p| format!(
+            "while copying path {:?} to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
+            p.display(),
+        );
+        "err existing to non-existing"
+    ),
 )]
-#[test_case(
-    tempfile::NamedTempFile::new().unwrap(),
-    |nft| nft.path(),
-    "/this/path/also/should/not/exist",
-    |p| format!(
-        "while processing path {:?}: with copy_to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
-        p.display(),
-    );
-    "err existing to non-existing"
+#[cfg_attr(
+    feature = "compat-messages",
+    test_case(
+        "/this/path/should/not/exist",
+        Path::new,
+        "/this/path/also/should/not/exist",
+        |p| format!(
+            "while processing path {:?}: with copy_to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
+            p.display(),
+        );
+        "err non-existing to non-existing"
+    ),
+    test_case(
+        tempfile::NamedTempFile::new().unwrap(),
+        |nft| nft.path(),
+        "/this/path/also/should/not/exist",
+        |p| format!(
+            "while processing path {:?}: with copy_to \"/this/path/also/should/not/exist\": No such file or directory (os error 2)",
+            p.display(),
+        );
+        "err existing to non-existing"
+    ),
 )]
 fn copy<T, IP, FMT>(input: T, into_path: IP, to: &str, fmt: FMT)
 where
@@ -249,11 +450,19 @@ fn hard_link((): ()) -> anyhow::Result<()> {
     assert_error_desc_eq(
         path.hard_link_anyhow(&link),
         // BUG: This error message is platform specific:
-        &format!(
-            "while processing path {:?}: with link_to {:?}: Permission denied (os error 13)",
-            path.display(),
-            link.display(),
-        ),
+        &if cfg!(feature = "compat-messages") {
+            format!(
+                "while processing path {:?}: with link_to {:?}: Permission denied (os error 13)",
+                path.display(),
+                link.display(),
+            )
+        } else {
+            format!(
+                "while hard-linking path {:?} to {:?}: Permission denied (os error 13)",
+                path.display(),
+                link.display(),
+            )
+        },
     );
     Ok(())
 }
@@ -358,11 +567,19 @@ fn rename((): ()) -> anyhow::Result<()> {
     assert_error_desc_eq(
         a.rename_anyhow(&b),
         // BUG: This error message is platform specific:
-        &format!(
-            "while processing path {:?}: with rename_to {:?}: Permission denied (os error 13)",
-            a.display(),
-            b.display(),
-        ),
+        &if cfg!(feature = "compat-messages") {
+            format!(
+                "while processing path {:?}: with rename_to {:?}: Permission denied (os error 13)",
+                a.display(),
+                b.display(),
+            )
+        } else {
+            format!(
+                "while renaming path {:?} to {:?}: Permission denied (os error 13)",
+                a.display(),
+                b.display(),
+            )
+        },
     );
     Ok(())
 }
@@ -388,10 +605,17 @@ fn write((): ()) -> anyhow::Result<()> {
     assert_error_desc_eq(
         path.write_anyhow("Hello World!"),
         // BUG: This error message is platform specific:
-        &format!(
-            "while writing to {:?}: Permission denied (os error 13)",
-            path.display(),
-        ),
+        &if cfg!(feature = "compat-messages") {
+            format!(
+                "while writing to {:?}: Permission denied (os error 13)",
+                path.display()
+            )
+        } else {
+            format!(
+                "while processing path {:?}: Permission denied (os error 13)",
+                path.display(),
+            )
+        },
     );
     Ok(())
 }
@@ -428,3 +652,1535 @@ fn open_file(input: &str) -> Result<(), String> {
 fn create_file(input: &str) -> Result<(), String> {
     stringify_error(Path::new(input).create_file_anyhow().map(|_| ()))
 }
+
+#[cfg(target_family = "unix")]
+#[test]
+fn split_prefix() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path().canonicalize_anyhow()?;
+    root.join("real/sub").create_dir_all_anyhow()?;
+    root.join("other").create_dir_anyhow()?;
+    std::os::unix::fs::symlink(root.join("real"), root.join("link"))?;
+
+    let (base, rest) = root
+        .join("real/sub")
+        .split_prefix_anyhow(root.join("link"))?;
+    assert_eq!(base, root.join("real"));
+    assert_eq!(rest, Path::new("sub"));
+
+    let replace = |e: anyhow::Error| format!("{:#}", e).replace(&root.display().to_string(), "DIR");
+    assert_eq!(
+        replace(
+            root.join("other")
+                .split_prefix_anyhow(root.join("link/sub"))
+                .err()
+                .unwrap()
+        ),
+        concat!(
+            r#"while processing path "DIR/other": with prefix "DIR/link/sub": "#,
+            r#"canonical path "DIR/other" diverges from canonical prefix "DIR/real/sub" after "DIR": "#,
+            r#""other" does not match "real""#,
+        )
+    );
+    assert_eq!(
+        replace(
+            root.join("real")
+                .split_prefix_anyhow(root.join("link/sub"))
+                .err()
+                .unwrap()
+        ),
+        concat!(
+            r#"while processing path "DIR/real": with prefix "DIR/link/sub": "#,
+            r#"canonical path "DIR/real" ends before canonical prefix "DIR/real/sub""#,
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn set_modified() -> anyhow::Result<()> {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file");
+    path.write_anyhow("x")?;
+    let mtime = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    path.set_modified_anyhow(mtime)?;
+    assert_eq!(path.metadata_anyhow()?.modified()?, mtime);
+
+    path.set_times_anyhow(Some(mtime), None)?;
+    assert_eq!(path.metadata_anyhow()?.accessed()?, mtime);
+
+    #[cfg(target_family = "unix")]
+    {
+        dir.path().set_modified_anyhow(mtime)?;
+        assert_eq!(dir.path().metadata_anyhow()?.modified()?, mtime);
+    }
+
+    let missing = dir.path().join("missing");
+    let err = missing.set_modified_anyhow(mtime).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", missing.display()), "PATH"),
+        "while processing path PATH: with modification time 1000000000s after the Unix epoch: No such file or directory (os error 2)"
+    );
+    let err = missing
+        .set_times_anyhow(Some(UNIX_EPOCH), Some(mtime))
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", missing.display()), "PATH"),
+        "while processing path PATH: with access time 0ns after the Unix epoch: with modification time 1000000000s after the Unix epoch: No such file or directory (os error 2)"
+    );
+    Ok(())
+}
+
+#[test]
+fn unique_path() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let a = dir.path().unique_path_anyhow("tmp-", ".part")?;
+    let b = dir.path().unique_path_anyhow("tmp-", ".part")?;
+    assert_ne!(a, b);
+    assert_eq!(a.read_anyhow()?, b"");
+    for path in [&a, &b] {
+        let name = path.file_name_anyhow()?.to_str_anyhow()?;
+        assert!(
+            name.starts_with("tmp-") && name.ends_with(".part"),
+            "{}",
+            name
+        );
+    }
+
+    let missing = dir.path().join("missing");
+    let err = missing.unique_path_anyhow("tmp-", "").err().unwrap();
+    assert!(format!("{:#}", err).ends_with("No such file or directory (os error 2)"));
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(crate::fs::SymlinkPolicy::Recreate => vec!["file", "link -> file", "sub", "sub/inner"]; "recreate")]
+#[test_case(crate::fs::SymlinkPolicy::Follow => vec!["file", "link", "sub", "sub/inner"]; "follow")]
+#[test_case(crate::fs::SymlinkPolicy::Skip => vec!["file", "sub", "sub/inner"]; "skip")]
+fn copy_dir_all(symlinks: crate::fs::SymlinkPolicy) -> Vec<String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let src = dir.path().join("src");
+    src.join("sub").create_dir_all_anyhow().unwrap();
+    src.join("file").write_anyhow("abc").unwrap();
+    src.join("sub/inner").write_anyhow("de").unwrap();
+    std::os::unix::fs::symlink("file", src.join("link")).unwrap();
+
+    let dst = dir.path().join("dst");
+    let options = crate::fs::CopyOptions::default().symlinks(symlinks);
+    let copied = src.copy_dir_all_anyhow(&dst, options).unwrap();
+    assert_eq!(dst.join("sub/inner").read_to_string_anyhow().unwrap(), "de");
+    let linked = if symlinks == crate::fs::SymlinkPolicy::Follow {
+        3
+    } else {
+        0
+    };
+    assert_eq!(copied, 5 + linked);
+
+    let mut names: Vec<String> = dst
+        .walk_anyhow()
+        .unwrap()
+        .map(|e| {
+            let path = e.unwrap().path();
+            let name = path.strip_prefix(&dst).unwrap().display().to_string();
+            match path.read_link() {
+                Ok(target) => format!("{} -> {}", name, target.display()),
+                Err(_) => name,
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn copy_dir_all_follow_broken_links() -> anyhow::Result<()> {
+    use crate::fs::{CopyOptions, SymlinkPolicy};
+
+    let dir = tempfile::TempDir::new()?;
+    let src = dir.path().join("src");
+    src.create_dir_anyhow()?;
+    std::os::unix::fs::symlink("missing", src.join("dangling"))?;
+    std::os::unix::fs::symlink("loop", src.join("loop"))?;
+
+    let dst = dir.path().join("dst");
+    let options = CopyOptions::default().symlinks(SymlinkPolicy::Follow);
+    let err = src.copy_dir_all_anyhow(&dst, options).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&src.display().to_string(), "SRC"),
+        r#"1 error:
+- while processing path "SRC/loop": Too many levels of symbolic links (os error 40)"#
+    );
+    assert_eq!(sorted_names(&dst)?, Vec::<String>::new());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn copy_dir_all_dir_symlink_at_destination() -> anyhow::Result<()> {
+    use crate::fs::CopyOptions;
+
+    let dir = tempfile::TempDir::new()?;
+    let src = dir.path().join("src");
+    src.join("sub").create_dir_all_anyhow()?;
+    src.join("sub/file").write_anyhow("abc")?;
+    let outside = dir.path().join("outside");
+    outside.create_dir_anyhow()?;
+    let dst = dir.path().join("dst");
+    dst.create_dir_anyhow()?;
+    std::os::unix::fs::symlink(&outside, dst.join("sub"))?;
+
+    let err = src
+        .copy_dir_all_anyhow(&dst, CopyOptions::default().overwrite(true))
+        .err()
+        .unwrap();
+    assert!(
+        format!("{:#}", err).starts_with("1 error:\n- while processing path "),
+        "{err:#}"
+    );
+    assert_eq!(sorted_names(&outside)?, Vec::<String>::new());
+    Ok(())
+}
+
+#[test]
+fn copy_dir_all_overwrite() -> anyhow::Result<()> {
+    use crate::fs::CopyOptions;
+
+    let dir = tempfile::TempDir::new()?;
+    let src = dir_with_files(&["a", "b"])?;
+    let dst = dir.path().join("dst");
+    dst.create_dir_anyhow()?;
+    dst.join("a").write_anyhow("old")?;
+
+    let err = src
+        .path()
+        .copy_dir_all_anyhow(&dst, CopyOptions::default())
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err)
+            .replace(&src.path().display().to_string(), "SRC")
+            .replace(&dst.display().to_string(), "DST"),
+        if cfg!(feature = "compat-messages") {
+            "1 error:\n- while processing path \"SRC/a\": with copy_to \"DST/a\": destination already exists"
+        } else {
+            "1 error:\n- while copying path \"SRC/a\" to \"DST/a\": destination already exists"
+        }
+    );
+    assert_eq!(dst.join("a").read_to_string_anyhow()?, "old");
+    assert!(dst.join("b").exists_no_follow_anyhow()?);
+
+    src.path()
+        .copy_dir_all_anyhow(&dst, CopyOptions::default().overwrite(true))?;
+    assert_eq!(
+        dst.join("a").read_to_string_anyhow()?,
+        src.path().join("a").read_to_string_anyhow()?
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn copy_dir_all_overwrite_symlink() -> anyhow::Result<()> {
+    use crate::fs::CopyOptions;
+
+    let dir = tempfile::TempDir::new()?;
+    let src = dir_with_files(&["a"])?;
+    let outside = dir.path().join("outside");
+    outside.write_anyhow("untouched")?;
+    let dst = dir.path().join("dst");
+    dst.create_dir_anyhow()?;
+    std::os::unix::fs::symlink(&outside, dst.join("a"))?;
+
+    src.path()
+        .copy_dir_all_anyhow(&dst, CopyOptions::default().overwrite(true))?;
+    assert!(!dst.join("a").symlink_metadata_anyhow()?.is_symlink());
+    assert_eq!(dst.join("a").read_to_string_anyhow()?, "a");
+    assert_eq!(outside.read_to_string_anyhow()?, "untouched");
+    Ok(())
+}
+
+#[test]
+fn copy_dir_all_into_itself() -> anyhow::Result<()> {
+    use crate::fs::CopyOptions;
+
+    let src = dir_with_files(&["a"])?;
+    let dst = src.path().join("sub/copy");
+    let err = src
+        .path()
+        .copy_dir_all_anyhow(&dst, CopyOptions::default())
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&src.path().display().to_string(), "SRC"),
+        if cfg!(feature = "compat-messages") {
+            r#"while processing path "SRC": with copy_to "SRC/sub/copy": cannot copy a directory into itself"#
+        } else {
+            r#"while copying path "SRC" to "SRC/sub/copy": cannot copy a directory into itself"#
+        }
+    );
+    assert_eq!(sorted_names(src.path())?, ["a"]);
+    Ok(())
+}
+
+#[test]
+fn append() -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("app.log");
+    path.append_anyhow()?.write_all(b"one\n")?;
+    path.append_anyhow()?.write_all(b"two\n")?;
+    assert_eq!(path.read_to_string_anyhow()?, "one\ntwo\n");
+
+    let err = dir.path().append_anyhow().err().unwrap();
+    assert!(format!("{:#}", err).starts_with("while processing path "));
+    Ok(())
+}
+
+#[test]
+fn rename_changing_case() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let lower = dir.path().join("foo.txt");
+    let upper = dir.path().join("Foo.txt");
+    lower.write_anyhow("hello")?;
+
+    lower.rename_changing_case_anyhow(&upper)?;
+
+    let names: Vec<_> = std::fs::read_dir(dir.path())?
+        .map(|e| e.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(names, ["Foo.txt"]);
+    assert_eq!(upper.read_to_string_anyhow()?, "hello");
+    Ok(())
+}
+
+#[cfg_attr(
+    not(feature = "compat-messages"),
+    test_case(
+        "/this/path/should/not/exist/foo.txt",
+        "/this/path/should/not/exist/Foo.txt"
+        => err_str(
+            r#"while renaming to temporary path (step 1 of 2): while renaming path "/this/path/should/not/exist/foo.txt" to "/this/path/should/not/exist/.foo.txt.case-rename.0": No such file or directory (os error 2)"#,
+        )
+        ; "case only"
+    ),
+    test_case(
+        "/this/path/should/not/exist/foo.txt",
+        "/this/path/should/not/exist/bar.txt"
+        => err_str(
+            r#"while renaming path "/this/path/should/not/exist/foo.txt" to "/this/path/should/not/exist/bar.txt": No such file or directory (os error 2)"#,
+        )
+        ; "not case only"
+    ),
+)]
+#[cfg_attr(
+    feature = "compat-messages",
+    test_case(
+        "/this/path/should/not/exist/foo.txt",
+        "/this/path/should/not/exist/Foo.txt"
+        => err_str(
+            r#"while renaming to temporary path (step 1 of 2): while processing path "/this/path/should/not/exist/foo.txt": with rename_to "/this/path/should/not/exist/.foo.txt.case-rename.0": No such file or directory (os error 2)"#,
+        )
+        ; "case only"
+    ),
+    test_case(
+        "/this/path/should/not/exist/foo.txt",
+        "/this/path/should/not/exist/bar.txt"
+        => err_str(
+            r#"while processing path "/this/path/should/not/exist/foo.txt": with rename_to "/this/path/should/not/exist/bar.txt": No such file or directory (os error 2)"#,
+        )
+        ; "not case only"
+    ),
+)]
+fn rename_changing_case_err(from: &str, to: &str) -> Result<(), String> {
+    stringify_error(Path::new(from).rename_changing_case_anyhow(to))
+}
+
+/// Rename like [std::fs::rename], except onto `Foo.txt`
+fn fail_onto_foo(from: &Path, to: &Path) -> std::io::Result<()> {
+    if to.file_name() == Some("Foo.txt".as_ref()) {
+        return Err(std::io::ErrorKind::PermissionDenied.into());
+    }
+    std::fs::rename(from, to)
+}
+
+/// Rename like [std::fs::rename], except onto names other than hidden ones
+fn fail_onto_visible(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !to.file_name().unwrap().to_string_lossy().starts_with('.') {
+        return Err(std::io::ErrorKind::PermissionDenied.into());
+    }
+    std::fs::rename(from, to)
+}
+
+#[test]
+fn rename_changing_case_restores() -> anyhow::Result<()> {
+    use super::caserename::rename_changing_case_with;
+
+    let dir = dir_with_files(&["foo.txt"])?;
+    let from = dir.path().join("foo.txt");
+    let err = rename_changing_case_with(&from, &dir.path().join("Foo.txt"), fail_onto_foo)
+        .err()
+        .unwrap();
+    assert!(
+        format!("{:#}", err).starts_with("while renaming from temporary path (step 2 of 2): "),
+        "{err:#}"
+    );
+    assert_eq!(sorted_names(dir.path())?, ["foo.txt"]);
+
+    let err = rename_changing_case_with(&from, &dir.path().join("Foo.txt"), fail_onto_visible)
+        .err()
+        .unwrap();
+    let msg = format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR");
+    assert!(
+        msg.starts_with(
+            r#"the file remains at "DIR/.foo.txt.case-rename.0", as renaming it back failed: "#
+        ),
+        "{msg}"
+    );
+    assert_eq!(sorted_names(dir.path())?, [".foo.txt.case-rename.0"]);
+    Ok(())
+}
+
+#[test]
+fn move_within_filesystem() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["a.txt"])?;
+    let sub = dir.path().join("sub");
+    sub.create_dir_anyhow()?;
+
+    dir.path().join("a.txt").move_anyhow(sub.join("b.txt"))?;
+    dir.path()
+        .join("sub")
+        .move_anyhow(dir.path().join("moved"))?;
+
+    assert_eq!(sorted_names(dir.path())?, ["moved"]);
+    assert_eq!(sorted_names(&dir.path().join("moved"))?, ["b.txt"]);
+    Ok(())
+}
+
+#[test]
+fn move_err_names_phase() {
+    assert_eq!(
+        stringify_error(Path::new("/this/path/should/not/exist").move_anyhow("/tmp/moved")),
+        err_str(if cfg!(feature = "compat-messages") {
+            r#"while processing path "/this/path/should/not/exist": with move_to "/tmp/moved": with phase rename: No such file or directory (os error 2)"#
+        } else {
+            r#"while moving path "/this/path/should/not/exist" to "/tmp/moved": with phase rename: No such file or directory (os error 2)"#
+        })
+    );
+}
+
+fn crosses_devices(_: &Path, _: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::CrossesDevices.into())
+}
+
+#[test]
+fn move_across_devices() -> anyhow::Result<()> {
+    use super::moving::move_with;
+
+    let dir = dir_with_files(&["a.txt"])?;
+    let sub = dir.path().join("sub");
+    sub.join("inner").create_dir_all_anyhow()?;
+    sub.join("inner/b.txt").write_anyhow("b")?;
+
+    move_with(
+        &dir.path().join("a.txt"),
+        &sub.join("a.txt"),
+        crosses_devices,
+    )?;
+    move_with(&sub, &dir.path().join("moved"), crosses_devices)?;
+    assert_eq!(sorted_names(dir.path())?, ["moved"]);
+    assert_eq!(sorted_names(&dir.path().join("moved"))?, ["a.txt", "inner"]);
+    assert_eq!(
+        dir.path()
+            .join("moved/inner/b.txt")
+            .read_to_string_anyhow()?,
+        "b"
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn move_across_devices_failed_copy() -> anyhow::Result<()> {
+    use super::moving::move_with;
+
+    let dir = tempfile::TempDir::new()?;
+    let src = dir.path().join("src");
+    src.create_dir_anyhow()?;
+    src.join("kept").write_anyhow("kept")?;
+    // Sockets cannot be opened for copying.
+    let _socket = std::os::unix::net::UnixListener::bind(src.join("socket"))?;
+
+    let err = move_with(&src, &dir.path().join("dst"), crosses_devices)
+        .err()
+        .unwrap();
+    let msg = format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR");
+    let prefix = if cfg!(feature = "compat-messages") {
+        r#"while processing path "DIR/src": with move_to "DIR/dst": with phase copy: 1 error:"#
+    } else {
+        r#"while moving path "DIR/src" to "DIR/dst": with phase copy: 1 error:"#
+    };
+    assert!(msg.starts_with(prefix), "{msg}");
+    assert_eq!(sorted_names(dir.path())?, ["src"]);
+    assert_eq!(sorted_names(&src)?, ["kept", "socket"]);
+    Ok(())
+}
+
+fn dir_with_files(names: &[&str]) -> anyhow::Result<tempfile::TempDir> {
+    let dir = tempfile::TempDir::new()?;
+    for name in names {
+        dir.path().join(name).write_anyhow(name)?;
+    }
+    Ok(dir)
+}
+
+fn sorted_names(dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut names = vec![];
+    for entry in dir.read_dir_anyhow()? {
+        names.push(entry?.file_name().to_str_anyhow()?.to_string());
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[test]
+fn rename_matching_ok() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["a.txt", "b.txt", "c.log"])?;
+
+    let renamed = dir.path().rename_matching_anyhow(|name| {
+        let name = name.to_str()?;
+        name.strip_suffix(".txt")
+            .map(|stem| format!("{stem}.md").into())
+    })?;
+
+    assert_eq!(renamed, 2);
+    assert_eq!(sorted_names(dir.path())?, ["a.md", "b.md", "c.log"]);
+    Ok(())
+}
+
+#[test]
+fn rename_matching_collisions() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["a", "b", "c", "d"])?;
+
+    let res = dir
+        .path()
+        .rename_matching_anyhow(|name| match name.to_str()? {
+            "a" | "b" => Some("x".into()),
+            "c" => Some("d".into()),
+            "d" => Some("sub/d".into()),
+            _ => None,
+        });
+
+    assert_error_desc_eq(
+        res,
+        &format!(
+            "while processing path {:?}: rename collisions detected; no entries were renamed: 3 errors:\n\
+             - entries \"a\" and \"b\" would both be renamed to \"x\"\n\
+             - entry \"c\" would be renamed to existing entry \"d\"\n\
+             - entry \"d\" would be renamed to invalid file name \"sub/d\"",
+            dir.path().display(),
+        ),
+    );
+    assert_eq!(sorted_names(dir.path())?, ["a", "b", "c", "d"]);
+    Ok(())
+}
+
+#[test]
+fn find_duplicates() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path();
+    let sub = root.join("sub");
+    sub.create_dir_anyhow()?;
+    root.join("a").write_anyhow("same")?;
+    sub.join("b").write_anyhow("same")?;
+    root.join("c").write_anyhow("diff")?;
+    root.join("d").write_anyhow("other length")?;
+    root.join("e").write_anyhow("")?;
+    sub.join("f").write_anyhow("")?;
+
+    assert_eq!(
+        root.find_duplicates_anyhow()?,
+        vec![vec![root.join("a"), sub.join("b")]]
+    );
+    Ok(())
+}
+
+#[test_case(
+    "/this/path/should/not/exist"
+    => err_str(
+        r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+    )
+    ; "missing"
+)]
+fn find_duplicates_err(input: &str) -> Result<Vec<Vec<std::path::PathBuf>>, String> {
+    stringify_error(Path::new(input).find_duplicates_anyhow())
+}
+
+#[test]
+fn largest_entries() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path();
+    let sub = root.join("sub");
+    sub.create_dir_anyhow()?;
+    root.join("small").write_anyhow("x")?;
+    root.join("medium").write_anyhow("xxx")?;
+    sub.join("large").write_anyhow("xxxxx")?;
+    sub.join("also-medium").write_anyhow("xxx")?;
+
+    assert_eq!(
+        root.largest_entries_anyhow(3)?,
+        vec![
+            (sub.join("large"), 5),
+            (root.join("medium"), 3),
+            (sub.join("also-medium"), 3),
+        ]
+    );
+    assert_eq!(root.largest_entries_anyhow(0)?, vec![]);
+    assert_eq!(root.largest_entries_anyhow(usize::MAX)?.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn remove_older_than() -> anyhow::Result<()> {
+    use std::time::{Duration, SystemTime};
+
+    let hour = Duration::from_secs(60 * 60);
+    let dir = tempfile::TempDir::new()?;
+    let old = dir.path().join("old");
+    let old_dir = dir.path().join("old-dir");
+    let new = dir.path().join("new");
+    new.write_anyhow("new")?;
+    old.write_anyhow("old")?;
+    old_dir.create_dir_anyhow()?;
+    old_dir.join("inner").write_anyhow("inner")?;
+    for p in [&old, &old_dir] {
+        std::fs::File::open(p)?.set_modified(SystemTime::now() - 2 * hour)?;
+    }
+
+    let expected = vec![old.clone(), old_dir.clone()];
+    assert_eq!(dir.path().remove_older_than_anyhow(hour, true)?, expected);
+    assert_eq!(sorted_names(dir.path())?, ["new", "old", "old-dir"]);
+    assert_eq!(dir.path().remove_older_than_anyhow(hour, false)?, expected);
+    assert_eq!(sorted_names(dir.path())?, ["new"]);
+    Ok(())
+}
+
+#[test]
+fn rotate() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let log = dir.path().join("app.log");
+    for content in ["first", "second", "third"] {
+        log.write_anyhow(content)?;
+        log.rotate_anyhow(2)?;
+    }
+
+    assert_eq!(sorted_names(dir.path())?, ["app.log.1", "app.log.2"]);
+    assert_eq!(
+        dir.path().join("app.log.1").read_to_string_anyhow()?,
+        "third"
+    );
+    assert_eq!(
+        dir.path().join("app.log.2").read_to_string_anyhow()?,
+        "second"
+    );
+
+    // Rotating a missing file only shifts generations:
+    log.rotate_anyhow(2)?;
+    assert_eq!(sorted_names(dir.path())?, ["app.log.2"]);
+    Ok(())
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn rotate_compressed() -> anyhow::Result<()> {
+    use std::io::Read;
+
+    let dir = tempfile::TempDir::new()?;
+    let log = dir.path().join("app.log");
+    log.write_anyhow("hello")?;
+    log.rotate_compressed_anyhow(3)?;
+
+    assert_eq!(sorted_names(dir.path())?, ["app.log.1.gz"]);
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(dir.path().join("app.log.1.gz").open_file_anyhow()?)
+        .read_to_string(&mut decoded)?;
+    assert_eq!(decoded, "hello");
+    Ok(())
+}
+
+#[test_case(crate::ErrorPolicy::FailFast, "" ; "fail fast")]
+#[test_case(crate::ErrorPolicy::Collect, "1 error:\n- " ; "collect")]
+fn for_each_entry(policy: crate::ErrorPolicy, prefix: &str) -> anyhow::Result<()> {
+    let dir = dir_with_files(&["a", "b", "c"])?;
+    let mut visited = vec![];
+    let res = dir.path().for_each_entry_anyhow(policy, |entry| {
+        let contents = entry.path().read_to_string_anyhow()?;
+        visited.push(contents.clone());
+        anyhow::ensure!(contents != "b", "rejected");
+        Ok(())
+    });
+
+    assert_error_desc_eq(
+        res,
+        &format!(
+            "{prefix}while processing path {:?}: with entry \"b\": rejected",
+            dir.path().display(),
+        ),
+    );
+    if policy == crate::ErrorPolicy::Collect {
+        visited.sort();
+        assert_eq!(visited, ["a", "b", "c"]);
+    }
+    Ok(())
+}
+
+#[test]
+fn map_dir() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["a", "bb"])?;
+    let mut sizes = dir
+        .path()
+        .map_dir_anyhow(crate::ErrorPolicy::FailFast, |entry| {
+            Ok(entry.metadata()?.len())
+        })?;
+    sizes.sort();
+    assert_eq!(sizes, [1, 2]);
+    Ok(())
+}
+
+#[test]
+fn collect_dir() -> anyhow::Result<()> {
+    use std::collections::BTreeMap;
+
+    let dir = dir_with_files(&["a", "bb", "ccc"])?;
+    let res: anyhow::Result<BTreeMap<String, String>> =
+        dir.path()
+            .collect_dir_anyhow(crate::ErrorPolicy::Collect, |entry| {
+                let name = entry.file_name().to_str_anyhow()?.to_string();
+                anyhow::ensure!(name.len() < 3, "name too long");
+                Ok((name, entry.path().read_to_string_anyhow()?))
+            });
+
+    let err = res.err().unwrap();
+    let errors = err.downcast_ref::<crate::ErrorCollection>().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        format!("{:#}", errors.iter().next().unwrap()),
+        format!(
+            "while processing path {:?}: with entry \"ccc\": name too long",
+            dir.path().display()
+        )
+    );
+
+    let ok: BTreeMap<String, String> =
+        dir.path()
+            .collect_dir_anyhow(crate::ErrorPolicy::FailFast, |entry| {
+                let name = entry.file_name().to_str_anyhow()?.to_string();
+                Ok((name.clone(), name))
+            })?;
+    assert_eq!(ok.keys().collect::<Vec<_>>(), ["a", "bb", "ccc"]);
+    Ok(())
+}
+
+#[test_case(PathAnyhow::ensure_dir_anyhow, "" => Ok(()); "dir ok")]
+#[test_case(
+    PathAnyhow::ensure_dir_anyhow,
+    "file"
+    => err_str("while processing path PATH: not a directory (found regular file)")
+    ; "dir found file"
+)]
+#[test_case(PathAnyhow::ensure_file_anyhow, "file" => Ok(()); "file ok")]
+#[test_case(
+    PathAnyhow::ensure_file_anyhow,
+    ""
+    => err_str("while processing path PATH: not a regular file (found directory)")
+    ; "file found dir"
+)]
+#[test_case(
+    PathAnyhow::ensure_file_anyhow,
+    "missing"
+    => err_str("while processing path PATH: No such file or directory (os error 2)")
+    ; "file missing"
+)]
+fn ensure_kind<F>(ensure: F, name: &str) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> anyhow::Result<crate::fs::Metadata>,
+{
+    let dir = tempfile::TempDir::new().unwrap();
+    dir.path().join("file").write_anyhow("").unwrap();
+    let path = dir.path().join(name);
+    stringify_error(ensure(&path).map(|_| ()))
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(crate::fs::FollowSymlinks::Yes => err_str(
+    "while processing path PATH: with follow symlinks yes: No such file or directory (os error 2)"
+); "follow dangling")]
+#[test_case(crate::fs::FollowSymlinks::No => Ok(true); "no follow dangling")]
+fn stat(follow: crate::fs::FollowSymlinks) -> Result<bool, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("dangling");
+    std::os::unix::fs::symlink(dir.path().join("missing"), &path).unwrap();
+    stringify_error(
+        path.stat_anyhow(follow)
+            .map(|md| md.file_type().is_symlink()),
+    )
+    .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test]
+fn classify() -> anyhow::Result<()> {
+    use crate::fs::FileKind;
+
+    let dir = tempfile::TempDir::new()?;
+    let file = dir.path().join("file");
+    file.write_anyhow("")?;
+
+    assert_eq!(dir.path().classify_anyhow()?, FileKind::Dir);
+    assert_eq!(file.classify_anyhow()?, FileKind::File);
+
+    #[cfg(target_family = "unix")]
+    {
+        let link = dir.path().join("link");
+        let dangling = dir.path().join("dangling");
+        std::os::unix::fs::symlink(&file, &link)?;
+        std::os::unix::fs::symlink(dir.path().join("missing"), &dangling)?;
+
+        assert_eq!(
+            link.classify_anyhow()?,
+            FileKind::Symlink(Some(Box::new(FileKind::File)))
+        );
+        assert_eq!(dangling.classify_anyhow()?, FileKind::Symlink(None));
+        assert_eq!(Path::new("/dev/null").classify_anyhow()?, FileKind::CharDev);
+    }
+    Ok(())
+}
+
+#[test_case(
+    "/this/path/should/not/exist"
+    => err_str(
+        r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+    )
+    ; "missing"
+)]
+fn classify_err(input: &str) -> Result<crate::fs::FileKind, String> {
+    stringify_error(Path::new(input).classify_anyhow())
+}
+
+#[test]
+fn exists_no_follow() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    assert!(dir.path().exists_no_follow_anyhow()?);
+    assert!(!dir.path().join("missing").exists_no_follow_anyhow()?);
+
+    #[cfg(target_family = "unix")]
+    {
+        let dangling = dir.path().join("dangling");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &dangling)?;
+        assert!(!dangling.exists());
+        assert!(dangling.exists_no_follow_anyhow()?);
+
+        let file = dir.path().join("file");
+        file.write_anyhow("")?;
+        assert_error_desc_eq(
+            file.join("child").exists_no_follow_anyhow(),
+            &format!(
+                "while processing path {:?}: Not a directory (os error 20)",
+                file.join("child").display()
+            ),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn find_broken_links() -> anyhow::Result<()> {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path();
+    let sub = root.join("sub");
+    sub.create_dir_anyhow()?;
+    root.join("file").write_anyhow("")?;
+    symlink("file", root.join("ok-link"))?;
+    symlink("missing", root.join("broken"))?;
+    symlink("../also-missing", sub.join("nested-broken"))?;
+    symlink("..", sub.join("dir-link"))?;
+
+    assert_eq!(
+        root.find_broken_links_anyhow()?,
+        vec![
+            (root.join("broken"), "missing".into()),
+            (sub.join("nested-broken"), "../also-missing".into()),
+        ]
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn find_world_writable() -> anyhow::Result<()> {
+    use std::fs::Permissions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path();
+    let sub = root.join("sub");
+    sub.create_dir_anyhow()?;
+    sub.set_permissions_anyhow(Permissions::from_mode(0o1777))?;
+    for (name, mode) in [("private", 0o600), ("shared", 0o666), ("setuid", 0o4755)] {
+        let path = sub.join(name);
+        path.write_anyhow("")?;
+        path.set_permissions_anyhow(Permissions::from_mode(mode))?;
+    }
+    std::os::unix::fs::symlink("private", sub.join("link"))?;
+
+    assert_eq!(
+        root.find_world_writable_anyhow()?,
+        vec![
+            (sub.clone(), 0o1777),
+            (sub.join("setuid"), 0o4755),
+            (sub.join("shared"), 0o666),
+        ]
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn chmod_recursive() -> anyhow::Result<()> {
+    use crate::fs::FileKind;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path().join("root");
+    root.join("sub").create_dir_all_anyhow()?;
+    root.join("sub/file").write_anyhow("")?;
+    root.join("top").write_anyhow("")?;
+    std::os::unix::fs::symlink("top", root.join("link"))?;
+    root.join("top").set_mode_anyhow(0o600)?;
+
+    root.chmod_recursive_anyhow(|kind| match kind {
+        FileKind::Dir => 0o750,
+        _ => 0o640,
+    })?;
+
+    let mode = |p: &str| -> anyhow::Result<u32> {
+        Ok(root.join(p).symlink_metadata_anyhow()?.permissions().mode() & 0o777)
+    };
+    assert_eq!(mode("")?, 0o750);
+    assert_eq!(mode("sub")?, 0o750);
+    assert_eq!(mode("sub/file")?, 0o640);
+    assert_eq!(mode("top")?, 0o640);
+    // The link's own mode is untouched.
+    assert_eq!(mode("link")?, 0o777);
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn chown_recursive() -> anyhow::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let root = dir.path().join("root");
+    root.join("sub").create_dir_all_anyhow()?;
+    root.join("sub/file").write_anyhow("")?;
+    let md = root.metadata_anyhow()?;
+
+    // Unchanged ownership needs no privileges and changes nothing.
+    assert!(root
+        .chown_recursive_anyhow(Some(md.uid()), Some(md.gid()), false)?
+        .is_empty());
+
+    let other = md.uid() + 1;
+    assert_eq!(
+        root.chown_recursive_anyhow(Some(other), None, true)?,
+        [root.clone(), root.join("sub"), root.join("sub/file")]
+    );
+    assert_eq!(root.join("sub/file").metadata_anyhow()?.uid(), md.uid());
+    Ok(())
+}
+
+#[test_case(b"plain text", None => Ok(("plain text".to_string(), vec![])); "valid")]
+#[test_case(
+    b"a\xffb\xf0\x9f\x98c",
+    None
+    => Ok(("a\u{FFFD}b\u{FFFD}c".to_string(), vec![1, 3]))
+    ; "replaced"
+)]
+#[test_case(b"a\xffb\xf0\x9f\x98c", Some(2) => Ok(("a\u{FFFD}b\u{FFFD}c".to_string(), vec![1, 3])); "within limit")]
+#[test_case(
+    b"a\xffb\xf0\x9f\x98c",
+    Some(1)
+    => err_str("while processing path PATH: found 2 invalid UTF-8 sequences, exceeding the limit of 1; byte offsets: 1, 3")
+    ; "over limit"
+)]
+fn read_to_string_lossy(
+    contents: &[u8],
+    max_replacements: Option<usize>,
+) -> Result<(String, Vec<usize>), String> {
+    let f = tempfile::NamedTempFile::new().unwrap();
+    f.path().write_anyhow(contents).unwrap();
+    stringify_error(f.path().read_to_string_lossy_anyhow(max_replacements))
+        .map_err(|e| e.replace(&format!("{:?}", f.path().display()), "PATH"))
+}
+
+#[cfg(feature = "encoding")]
+#[test_case(b"plain" => Ok("plain".to_string()); "utf8 no bom")]
+#[test_case(b"\xef\xbb\xbfkey = 1" => Ok("key = 1".to_string()); "utf8 bom")]
+#[test_case(b"\xff\xfek\x00=\x001\x00" => Ok("k=1".to_string()); "utf16le bom")]
+#[test_case(b"\xfe\xff\x00k\x00=\x001" => Ok("k=1".to_string()); "utf16be bom")]
+#[test_case(
+    b"\xef\xbb\xbfok \xff"
+    => err_str("while processing path PATH: invalid UTF-8 at byte offset 6")
+    ; "invalid utf8"
+)]
+#[test_case(
+    b"\xff\xfek\x00\x00\xdc"
+    => err_str("while processing path PATH: invalid UTF-16LE at byte offset 4")
+    ; "unpaired surrogate"
+)]
+fn read_text(contents: &[u8]) -> Result<String, String> {
+    let f = tempfile::NamedTempFile::new().unwrap();
+    f.path().write_anyhow(contents).unwrap();
+    stringify_error(f.path().read_text_anyhow())
+        .map_err(|e| e.replace(&format!("{:?}", f.path().display()), "PATH"))
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn read_text_encoded() -> anyhow::Result<()> {
+    let f = tempfile::NamedTempFile::new()?;
+    f.path().write_anyhow(b"caf\xe9")?;
+    assert_eq!(
+        f.path()
+            .read_text_encoded_anyhow(encoding_rs::WINDOWS_1252)?,
+        "caf\u{e9}"
+    );
+    Ok(())
+}
+
+#[test]
+fn read_text_normalized() -> anyhow::Result<()> {
+    let f = tempfile::NamedTempFile::new()?;
+    f.path().write_anyhow("a\r\nb\nc\r\n")?;
+    assert_eq!(f.path().read_text_normalized_anyhow()?, "a\nb\nc\n");
+    Ok(())
+}
+
+#[test_case(crate::fs::LineEnding::Lf => "a\nb\nc"; "lf")]
+#[test_case(crate::fs::LineEnding::CrLf => "a\r\nb\r\nc"; "crlf")]
+#[cfg_attr(windows, test_case(crate::fs::LineEnding::Native => "a\r\nb\r\nc"; "native"))]
+#[cfg_attr(not(windows), test_case(crate::fs::LineEnding::Native => "a\nb\nc"; "native"))]
+fn write_text(eol: crate::fs::LineEnding) -> String {
+    let f = tempfile::NamedTempFile::new().unwrap();
+    f.path().write_text_anyhow("a\r\nb\nc", eol).unwrap();
+    f.path().read_to_string_anyhow().unwrap()
+}
+
+#[test]
+fn write_slices() -> anyhow::Result<()> {
+    use std::io::IoSlice;
+
+    let f = tempfile::NamedTempFile::new()?;
+    f.path().write_slices_anyhow(&[
+        IoSlice::new(b"header\n"),
+        IoSlice::new(b""),
+        IoSlice::new(b"body\n"),
+    ])?;
+    assert_eq!(f.path().read_to_string_anyhow()?, "header\nbody\n");
+    Ok(())
+}
+
+#[test_case(0 => "with slice index 0 after 0 bytes written: failed to write whole buffer"; "first")]
+#[test_case(4 => "with slice index 1 after 4 bytes written: failed to write whole buffer"; "boundary")]
+#[test_case(6 => "with slice index 3 after 6 bytes written: failed to write whole buffer"; "empty skipped")]
+fn write_all_vectored_err(capacity: usize) -> String {
+    use std::io::IoSlice;
+
+    let mut buf = [0u8; 16];
+    let mut w = &mut buf[..capacity];
+    let slices = [
+        IoSlice::new(b"abcd"),
+        IoSlice::new(b"ef"),
+        IoSlice::new(b""),
+        IoSlice::new(b"gh"),
+    ];
+    let err = super::write::write_all_vectored(&mut w, &slices)
+        .err()
+        .unwrap();
+    format!("{:#}", err)
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_slices_err() {
+    use std::io::IoSlice;
+
+    assert_error_desc_eq(
+        Path::new("/dev/full").write_slices_anyhow(&[IoSlice::new(b"x")]),
+        r#"while processing path "/dev/full": with slice index 0 after 0 bytes written: No space left on device (os error 28)"#,
+    );
+}
+
+#[test_case(crate::fs::WriteMode::Truncate, true => Ok("new".to_string()); "truncate existing")]
+#[test_case(crate::fs::WriteMode::Truncate, false => Ok("new".to_string()); "truncate missing")]
+#[test_case(crate::fs::WriteMode::Append, true => Ok("oldnew".to_string()); "append existing")]
+#[test_case(crate::fs::WriteMode::Append, false => Ok("new".to_string()); "append missing")]
+#[test_case(crate::fs::WriteMode::CreateNew, false => Ok("new".to_string()); "create new missing")]
+#[test_case(
+    crate::fs::WriteMode::CreateNew,
+    true
+    => err_str("while processing path PATH: with write mode create-new: File exists (os error 17)")
+    ; "create new existing"
+)]
+#[test_case(crate::fs::WriteMode::MustExist, true => Ok("new".to_string()); "must exist existing")]
+#[test_case(
+    crate::fs::WriteMode::MustExist,
+    false
+    => err_str("while processing path PATH: with write mode must-exist: No such file or directory (os error 2)")
+    ; "must exist missing"
+)]
+fn write_mode(mode: crate::fs::WriteMode, exists: bool) -> Result<String, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    if exists {
+        path.write_anyhow("old").unwrap();
+    }
+    stringify_error(
+        path.write_mode_anyhow("new", mode)
+            .and_then(|()| path.read_to_string_anyhow()),
+    )
+    .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test_case(false => Ok("new".to_string()); "missing")]
+#[test_case(
+    true
+    => err_str("while processing path PATH: refusing to overwrite existing file: File exists (os error 17)")
+    ; "existing"
+)]
+fn write_new(exists: bool) -> Result<String, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    if exists {
+        path.write_anyhow("old").unwrap();
+    }
+    stringify_error(
+        path.write_new_anyhow("new")
+            .and_then(|()| path.read_to_string_anyhow()),
+    )
+    .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test]
+fn write_with_backup() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("config");
+    let unrelated = dir.path().join("config.bak.tmp");
+    unrelated.write_anyhow("kept")?;
+
+    assert_eq!(path.write_with_backup_anyhow("one", ".bak")?, None);
+    let backup = path.write_with_backup_anyhow("two", ".bak")?.unwrap();
+    assert_eq!(backup, dir.path().join("config.bak"));
+    assert_eq!(backup.read_to_string_anyhow()?, "one");
+
+    path.write_with_backup_anyhow("three", ".bak")?;
+    assert_eq!(backup.read_to_string_anyhow()?, "two");
+    assert_eq!(path.read_to_string_anyhow()?, "three");
+    assert_eq!(unrelated.read_to_string_anyhow()?, "kept");
+    assert_eq!(
+        sorted_names(dir.path())?,
+        ["config", "config.bak", "config.bak.tmp"]
+    );
+    Ok(())
+}
+
+#[test]
+fn write_with_backup_failed_backup() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("sub");
+    path.create_dir_anyhow().unwrap();
+
+    let err = path.write_with_backup_anyhow("x", ".bak").err().unwrap();
+    let dir_str = dir.path().display().to_string();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir_str, "DIR"),
+        if cfg!(feature = "compat-messages") {
+            r#"while processing path "DIR/sub": with backup_to "DIR/sub.bak": the source path is neither a regular file nor a symlink to a regular file"#
+        } else {
+            r#"while backing up path "DIR/sub" to "DIR/sub.bak": the source path is neither a regular file nor a symlink to a regular file"#
+        }
+    );
+}
+
+#[test_case("a\nport = 80\n" => Ok(()); "equal")]
+#[test_case(
+    "a\nport = 8080\n"
+    => err_str(r#"while processing path PATH: contents differ at line 2, byte offset 11: expected "port = 8080\n", found "port = 80\n""#)
+    ; "differing line"
+)]
+#[test_case(
+    "a\n"
+    => err_str(r#"while processing path PATH: contents differ at line 2, byte offset 2: expected end of file, found "port = 80\n""#)
+    ; "longer"
+)]
+#[test_case(
+    "a\nport = 80\nhost = x\n"
+    => err_str(r#"while processing path PATH: contents differ at line 3, byte offset 12: expected "host = x\n", found end of file"#)
+    ; "shorter"
+)]
+fn expect_contents(expected: &str) -> Result<(), String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    path.write_anyhow("a\nport = 80\n").unwrap();
+    stringify_error(path.expect_contents_anyhow(expected))
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test_case("port" => Ok(()); "found")]
+#[test_case(
+    "host"
+    => err_str(r#"while processing path PATH: contents do not contain "host" in 12 bytes"#)
+    ; "missing"
+)]
+fn expect_contains(needle: &str) -> Result<(), String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    path.write_anyhow("a\nport = 80\n").unwrap();
+    stringify_error(path.expect_contains_anyhow(needle))
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test]
+fn manifest() -> anyhow::Result<()> {
+    use crate::fs::ManifestEntry;
+
+    let dir = dir_with_files(&["b.txt", "a.txt"])?;
+    dir.path().join("sub").create_dir_anyhow()?;
+    dir.path().join("sub/c").write_anyhow("hello")?;
+
+    let entries = dir.path().manifest_anyhow()?.entries;
+    let expected = [("a.txt", 5), ("b.txt", 5), ("sub/c", 5)].map(|(path, size)| ManifestEntry {
+        path: path.into(),
+        size,
+        sha256: None,
+    });
+    assert_eq!(entries, expected);
+
+    #[cfg(feature = "sha2")]
+    assert_eq!(
+        dir.path().manifest_sha256_anyhow()?.entries[2]
+            .sha256
+            .as_deref(),
+        Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_manifest() -> anyhow::Result<()> {
+    use crate::fs::ManifestDiff;
+
+    let dir = dir_with_files(&["kept", "grown", "removed"])?;
+    #[cfg(feature = "sha2")]
+    let manifest = dir.path().manifest_sha256_anyhow()?;
+    #[cfg(not(feature = "sha2"))]
+    let manifest = dir.path().manifest_anyhow()?;
+    assert!(dir.path().verify_manifest_anyhow(&manifest)?.is_empty());
+
+    dir.path().join("grown").write_anyhow("grown larger")?;
+    dir.path().join("removed").remove_file_anyhow()?;
+    dir.path().join("added").write_anyhow("added")?;
+    assert_eq!(
+        dir.path().verify_manifest_anyhow(&manifest)?,
+        ManifestDiff {
+            missing: vec!["removed".into()],
+            extra: vec!["added".into()],
+            modified: vec!["grown".into()],
+        }
+    );
+
+    #[cfg(feature = "sha2")]
+    {
+        dir.path().join("kept").write_anyhow("KEPT")?;
+        assert!(dir
+            .path()
+            .verify_manifest_anyhow(&manifest)?
+            .modified
+            .contains(&"kept".into()));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "sha2"))]
+#[test]
+fn verify_manifest_digests_without_sha2() -> anyhow::Result<()> {
+    use crate::fs::{Manifest, ManifestEntry};
+
+    let dir = dir_with_files(&["file"])?;
+    let manifest = Manifest {
+        entries: vec![ManifestEntry {
+            path: "file".into(),
+            size: 4,
+            sha256: Some("00".repeat(32)),
+        }],
+    };
+    assert_eq!(
+        format!("{:#}", dir.path().verify_manifest_anyhow(&manifest).err().unwrap())
+            .replace(&format!("{:?}", dir.path().display()), "DIR"),
+        "while processing path DIR: manifest records sha256 digests but the `sha2` feature is disabled"
+    );
+    Ok(())
+}
+
+#[test_case(5, 5 => Ok(5); "at limit")]
+#[test_case(
+    5, 4
+    => err_str("while processing path PATH: file is 5 B, limit is 4 B")
+    ; "over limit"
+)]
+#[test_case(
+    2048, 1024
+    => err_str("while processing path PATH: file is 2 KiB, limit is 1 KiB")
+    ; "over limit in KiB"
+)]
+fn read_to_string_limited(len: usize, max_bytes: u64) -> Result<usize, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("file");
+    path.write_anyhow("x".repeat(len)).unwrap();
+    stringify_error(
+        path.read_to_string_limited_anyhow(max_bytes)
+            .map(|s| s.len()),
+    )
+    .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[test]
+fn write_atomic() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("config");
+
+    path.write_atomic_anyhow("one")?;
+    path.write_atomic_anyhow("two")?;
+    assert_eq!(path.read_to_string_anyhow()?, "two");
+    assert_eq!(sorted_names(dir.path())?, ["config"]);
+    Ok(())
+}
+
+#[test]
+fn write_atomic_err_removes_temp() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("sub");
+    path.create_dir_anyhow().unwrap();
+
+    let err = path.write_atomic_anyhow("x").err().unwrap();
+    let msg = format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR");
+    assert!(
+        msg.starts_with(r#"while processing path "DIR/sub": with temporary path "DIR/.sub."#),
+        "{msg}"
+    );
+    assert!(
+        msg.ends_with(r#".tmp": Is a directory (os error 21)"#),
+        "{msg}"
+    );
+    assert_eq!(sorted_names(dir.path()).unwrap(), ["sub"]);
+}
+
+#[test_case(&[] => Ok("report.txt".to_string()); "free")]
+#[test_case(&["report.txt"] => Ok("report (1).txt".to_string()); "taken")]
+#[test_case(&["report.txt", "report (1).txt"] => Ok("report (2).txt".to_string()); "two taken")]
+#[test_case(
+    &["report.txt", "report (1).txt", "report (2).txt"]
+    => err_str(r#"while processing path "DIR/report.txt": no available name for "report" in directory "DIR" after 2 attempts"#)
+    ; "exhausted"
+)]
+fn next_available_name(existing: &[&str]) -> Result<String, String> {
+    let dir = dir_with_files(existing).unwrap();
+    let dir_str = dir.path().display().to_string();
+    stringify_error(
+        dir.path()
+            .join("report.txt")
+            .next_available_name_anyhow(2)
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned()),
+    )
+    .map_err(|e| e.replace(&dir_str, "DIR"))
+}
+
+#[test]
+fn next_available_name_without_extension() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["notes"])?;
+    let path = dir.path().join("notes").next_available_name_anyhow(1)?;
+    assert_eq!(path, dir.path().join("notes (1)"));
+    Ok(())
+}
+
+#[test]
+fn next_available_name_context_not_repeated() {
+    let err = Path::new("/").next_available_name_anyhow(1).err();
+    // "/" exists and has no file stem; the path context must appear only once.
+    assert_eq!(
+        format!("{:#}", err.unwrap()),
+        r#"while processing path "/": missing expected filename"#
+    );
+}
+
+#[test]
+fn extract_assets() -> anyhow::Result<()> {
+    let dir = dir_with_files(&["same"])?;
+    let assets: &[(&str, &[u8])] = &[
+        ("same", b"same"),
+        ("templates/new.txt", b"new"),
+        ("../escape", b"x"),
+    ];
+
+    let err = dir.path().extract_assets_anyhow(assets).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err),
+        "1 error:\n- with asset \"../escape\": asset names must be relative paths without \"..\""
+    );
+    assert_eq!(
+        dir.path()
+            .join("templates/new.txt")
+            .read_to_string_anyhow()?,
+        "new"
+    );
+
+    let report = dir.path().extract_assets_anyhow(&assets[..2])?;
+    assert!(report.written.is_empty());
+    assert_eq!(
+        report.unchanged,
+        [
+            dir.path().join("same"),
+            dir.path().join("templates/new.txt")
+        ]
+    );
+
+    dir.path().join("same").write_anyhow("changed")?;
+    let report = dir.path().extract_assets_anyhow(&assets[..1])?;
+    assert_eq!(report.written, [dir.path().join("same")]);
+    assert_eq!(dir.path().join("same").read_to_string_anyhow()?, "same");
+    Ok(())
+}
+
+#[cfg(feature = "hex")]
+#[test_case("00ff10\n" => Ok(vec![0x00, 0xff, 0x10]); "ok")]
+#[test_case("0A0b" => Ok(vec![0x0a, 0x0b]); "mixed case")]
+#[test_case(
+    "00fg"
+    => err_str(r#"while processing path PATH: invalid hex digit 'g' at character offset 3"#)
+    ; "invalid digit"
+)]
+#[test_case(
+    "abc\n"
+    => err_str(r#"while processing path PATH: odd number of hex digits: 3"#)
+    ; "odd"
+)]
+fn read_hex(contents: &str) -> Result<Vec<u8>, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("key.hex");
+    path.write_anyhow(contents).unwrap();
+    stringify_error(path.read_hex_anyhow())
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[cfg(feature = "base64")]
+#[test_case("aGVsbG8=\n" => Ok(b"hello".to_vec()); "ok")]
+#[test_case(
+    "aGV!bG8="
+    => err_str(r#"while processing path PATH: Invalid symbol 33, offset 3."#)
+    ; "invalid symbol"
+)]
+fn read_base64(contents: &str) -> Result<Vec<u8>, String> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("token.b64");
+    path.write_anyhow(contents).unwrap();
+    stringify_error(path.read_base64_anyhow())
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[cfg(all(feature = "hex", feature = "base64"))]
+#[test]
+fn write_hex_and_base64_round_trip() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let bytes = [0u8, 1, 0xfe, 0xff];
+
+    let hex = dir.path().join("key.hex");
+    hex.write_hex_anyhow(&bytes)?;
+    assert_eq!(hex.read_to_string_anyhow()?, "0001feff\n");
+    assert_eq!(hex.read_hex_anyhow()?, bytes);
+
+    let b64 = dir.path().join("key.b64");
+    b64.write_base64_anyhow(&bytes)?;
+    assert_eq!(b64.read_to_string_anyhow()?, "AAH+/w==\n");
+    assert_eq!(b64.read_base64_anyhow()?, bytes);
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(0o600, 0o600 => Ok(b"key".to_vec()); "owner only")]
+#[test_case(0o400, 0o600 => Ok(b"key".to_vec()); "stricter")]
+#[test_case(
+    0o644, 0o600
+    => err_str("while processing path PATH: permissions 0o644 are too open; at most 0o600 is allowed")
+    ; "too open"
+)]
+#[test_case(0o640, 0o640 => Ok(b"key".to_vec()); "configured")]
+fn read_secret(mode: u32, allowed: u32) -> Result<Vec<u8>, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("id_key");
+    path.write_anyhow("key").unwrap();
+    path.set_permissions_anyhow(std::fs::Permissions::from_mode(mode))
+        .unwrap();
+    stringify_error(path.read_secret_anyhow(allowed))
+        .map_err(|e| e.replace(&format!("{:?}", path.display()), "PATH"))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn create_dir_with_mode() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let private = dir.path().join("private");
+    private.create_private_dir_anyhow()?;
+    assert_eq!(
+        private.metadata_anyhow()?.permissions().mode() & 0o777,
+        0o700
+    );
+
+    let err = private.create_dir_with_mode_anyhow(0o750).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", private.display()), "PATH"),
+        "while processing path PATH: with mode 0o750: File exists (os error 17)"
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn set_mode() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("file");
+    path.write_anyhow("x")?;
+    path.set_mode_anyhow(0o640)?;
+    assert_eq!(path.metadata_anyhow()?.permissions().mode() & 0o777, 0o640);
+
+    let missing = dir.path().join("missing");
+    let err = missing.set_mode_anyhow(0o640).err().unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&format!("{:?}", missing.display()), "PATH"),
+        "while processing path PATH: with mode 0o640: No such file or directory (os error 2)"
+    );
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::context::with_path_context;
+use crate::{ByteSize, PathAnyhow};
+use anyhow::anyhow;
+use std::path::Path;
+
+const OFFSET_DISPLAY_LIMIT: usize = 10;
+
+pub(super) fn read_to_string_limited(path: &Path, max_bytes: u64) -> anyhow::Result<String> {
+    use std::io::Read;
+
+    // Read one byte past the limit to detect an oversized file even if its size is misreported.
+    let mut bytes = vec![];
+    let f = path.open_file_anyhow()?;
+    with_path_context(
+        f.take(max_bytes.saturating_add(1)).read_to_end(&mut bytes),
+        path,
+    )?;
+    if bytes.len() as u64 > max_bytes {
+        let size = match path.metadata_anyhow() {
+            Ok(md) if md.len() > max_bytes => ByteSize(md.len()).to_string(),
+            _ => format!("more than {}", ByteSize(max_bytes)),
+        };
+        return with_path_context(
+            Err(anyhow!("file is {size}, limit is {}", ByteSize(max_bytes))),
+            path,
+        );
+    }
+    with_path_context(String::from_utf8(bytes), path)
+}
+
+pub(super) fn read_to_string_lossy(
+    path: &Path,
+    max_replacements: Option<usize>,
+) -> anyhow::Result<(String, Vec<usize>)> {
+    let bytes = path.read_anyhow()?;
+    let (text, offsets) = decode_lossy(&bytes);
+    match max_replacements {
+        Some(max) if offsets.len() > max => with_path_context(
+            Err(anyhow!(
+                "found {} invalid UTF-8 sequences, exceeding the limit of {}; byte offsets: {}",
+                offsets.len(),
+                max,
+                render_offsets(&offsets),
+            )),
+            path,
+        ),
+        _ => Ok((text, offsets)),
+    }
+}
+
+/// Like [String::from_utf8_lossy], also returning the byte offset of each replaced sequence
+fn decode_lossy(bytes: &[u8]) -> (String, Vec<usize>) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut offsets = vec![];
+    let mut pos = 0;
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        pos += chunk.valid().len();
+        if !chunk.invalid().is_empty() {
+            text.push(char::REPLACEMENT_CHARACTER);
+            offsets.push(pos);
+            pos += chunk.invalid().len();
+        }
+    }
+    (text, offsets)
+}
+
+fn render_offsets(offsets: &[usize]) -> String {
+    let mut rendered: Vec<String> = offsets
+        .iter()
+        .take(OFFSET_DISPLAY_LIMIT)
+        .map(usize::to_string)
+        .collect();
+    if offsets.len() > OFFSET_DISPLAY_LIMIT {
+        rendered.push("\u{2026}".to_string());
+    }
+    rendered.join(", ")
+}
+
+#[cfg(feature = "encoding")]
+pub(super) fn read_text(
+    path: &Path,
+    fallback: &'static encoding_rs::Encoding,
+) -> anyhow::Result<String> {
+    let bytes = path.read_anyhow()?;
+    with_path_context(decode_with_bom(&bytes, fallback), path)
+}
+
+/// Decode `bytes` according to its BOM, or `fallback` if there is none, without replacement
+#[cfg(feature = "encoding")]
+fn decode_with_bom(
+    bytes: &[u8],
+    fallback: &'static encoding_rs::Encoding,
+) -> anyhow::Result<String> {
+    use encoding_rs::{DecoderResult, Encoding};
+
+    let (encoding, bom_len) = Encoding::for_bom(bytes).unwrap_or((fallback, 0));
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::new();
+    let mut pos = bom_len;
+    loop {
+        let src = &bytes[pos..];
+        text.reserve(
+            decoder
+                .max_utf8_buffer_length_without_replacement(src.len())
+                .unwrap_or(src.len()),
+        );
+        let (result, read) = decoder.decode_to_string_without_replacement(src, &mut text, true);
+        pos += read;
+        match result {
+            DecoderResult::InputEmpty => return Ok(text),
+            DecoderResult::OutputFull => continue,
+            DecoderResult::Malformed(bad, extra) => {
+                return Err(anyhow!(
+                    "invalid {} at byte offset {}",
+                    encoding.name(),
+                    pos - usize::from(bad) - usize::from(extra),
+                ));
+            }
+        }
+    }
+}
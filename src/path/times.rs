@@ -0,0 +1,53 @@
+use crate::audit::audited;
+use crate::context::{value_context, with_path_context};
+use anyhow::Context;
+use std::fs::{File, FileTimes};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(super) fn set_times(
+    path: &Path,
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+) -> anyhow::Result<()> {
+    let mut times = FileTimes::new();
+    if let Some(t) = accessed {
+        times = times.set_accessed(t);
+    }
+    if let Some(t) = modified {
+        times = times.set_modified(t);
+    }
+    audited("set_times", &[path], || {
+        let mut res = open(path)
+            .and_then(|f| f.set_times(times))
+            .map_err(anyhow::Error::from);
+        if let Some(t) = modified {
+            res = res.context(value_context("modification time", describe(t)));
+        }
+        if let Some(t) = accessed {
+            res = res.context(value_context("access time", describe(t)));
+        }
+        with_path_context(res, path)
+    })
+}
+
+/// Open `path` with the least access the platform needs to set its times
+fn open(path: &Path) -> std::io::Result<File> {
+    // Unix sets times through any descriptor, so read-only files and directories work too.
+    #[cfg(target_family = "unix")]
+    {
+        File::open(path)
+    }
+
+    #[cfg(not(target_family = "unix"))]
+    {
+        std::fs::OpenOptions::new().write(true).open(path)
+    }
+}
+
+fn describe(time: SystemTime) -> String {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => format!("{:?} after the Unix epoch", d),
+        Err(e) => format!("{:?} before the Unix epoch", e.duration()),
+    }
+}
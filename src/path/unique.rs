@@ -0,0 +1,44 @@
+use crate::context::with_path_context;
+use crate::escape_os;
+use crate::fs::WriteMode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+const MAX_ATTEMPTS: usize = 16;
+
+pub(super) fn unique_path(dir: &Path, prefix: &str, suffix: &str) -> anyhow::Result<PathBuf> {
+    for _ in 0..MAX_ATTEMPTS {
+        let path = dir.join(format!("{prefix}{:016x}{suffix}", random_component()));
+        match WriteMode::CreateNew.open_options().open(&path) {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return with_path_context(Err(e), &path),
+        }
+    }
+    with_path_context(
+        Err(anyhow::anyhow!(
+            "no unique path with prefix {} and suffix {} after {MAX_ATTEMPTS} attempts",
+            escape_os(prefix),
+            escape_os(suffix),
+        )),
+        dir,
+    )
+}
+
+/// A value unpredictable to other processes, without a dependency on a random number generator
+fn random_component() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // Each `RandomState` is seeded with fresh random keys.
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    if let Ok(d) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(d.as_nanos());
+    }
+    hasher.finish()
+}
@@ -0,0 +1,170 @@
+use crate::audit::audited;
+use crate::context::{
+    render_path, value_context, with_path_context, with_two_path_context, Operation,
+};
+use crate::fs::WriteMode;
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::fs::File;
+use std::io::{ErrorKind, IoSlice, Write};
+use std::path::{Path, PathBuf};
+
+pub(super) fn write_slices(path: &Path, slices: &[IoSlice<'_>]) -> anyhow::Result<()> {
+    audited("write", &[path], || {
+        let mut f = with_path_context(File::create(path), path)?;
+        with_path_context(write_all_vectored(&mut f, slices), path)
+    })
+}
+
+pub(super) fn write_mode(path: &Path, contents: &[u8], mode: WriteMode) -> anyhow::Result<()> {
+    audited("write", &[path], || {
+        let mut f = with_path_context(
+            mode.open_options()
+                .open(path)
+                .with_context(|| value_context("write mode", mode)),
+            path,
+        )?;
+        with_path_context(f.write_all(contents), path)
+    })
+}
+
+pub(super) fn write_new(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    audited("write", &[path], || {
+        let res = WriteMode::CreateNew.open_options().open(path);
+        let res = match res {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                Err(e).context("refusing to overwrite existing file")
+            }
+            res => res.with_context(|| value_context("write mode", WriteMode::CreateNew)),
+        };
+        let mut f = with_path_context(res, path)?;
+        with_path_context(f.write_all(contents), path)
+    })
+}
+
+pub(super) fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    audited("write_atomic", &[path], || {
+        let tmp = temp_sibling(path, ".tmp")?;
+
+        let res = replace_with(&tmp, path, contents);
+        if res.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        with_path_context(
+            res.with_context(|| value_context("temporary path", render_path(&tmp))),
+            path,
+        )
+    })
+}
+
+/// Create a new, empty file beside `path` to be renamed over it, named `.<name>.<random><suffix>`
+///
+/// Being created exclusively, the file never clobbers an existing one, whatever its name.
+pub(crate) fn temp_sibling(path: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    // The temporary name only needs to resemble the target, so a lossy name is fine.
+    let prefix = format!(".{}.", path.file_name_anyhow()?.to_string_lossy());
+    with_path_context(super::unique::unique_path(dir, &prefix, suffix), path)
+}
+
+/// Fill `tmp` with `contents`, keeping the permissions of any existing `path`, then rename it over
+/// `path`
+fn replace_with(tmp: &Path, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut f = std::fs::OpenOptions::new().write(true).open(tmp)?;
+    f.write_all(contents)?;
+    if let Ok(md) = std::fs::metadata(path) {
+        f.set_permissions(md.permissions())?;
+    }
+    f.sync_all()?;
+    drop(f);
+    std::fs::rename(tmp, path)?;
+
+    // Persist the rename itself; directories cannot be opened this way on every platform.
+    #[cfg(target_family = "unix")]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+pub(super) fn write_with_backup(
+    path: &Path,
+    contents: &[u8],
+    suffix: &str,
+) -> anyhow::Result<Option<PathBuf>> {
+    let backup = if path.exists_no_follow_anyhow()? {
+        let backup = sibling_with_suffix(path, suffix)?;
+        audited("backup", &[path, &backup], || {
+            let tmp = temp_sibling(path, &format!("{suffix}.tmp"))?;
+            // Copy then rename so an existing backup is never left half-written.
+            let res = std::fs::copy(path, &tmp).and_then(|_| std::fs::rename(&tmp, &backup));
+            if res.is_err() {
+                let _ = std::fs::remove_file(&tmp);
+            }
+            with_two_path_context(res, Operation::Backup, path, &backup)
+        })?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    path.write_anyhow(contents).with_context(|| match &backup {
+        Some(backup) => format!("after backing up to {}", render_path(backup)),
+        None => "with no existing file to back up".to_string(),
+    })?;
+    Ok(backup)
+}
+
+fn sibling_with_suffix(path: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+    let mut name = path.file_name_anyhow()?.to_os_string();
+    name.push(suffix);
+    Ok(path.with_file_name(name))
+}
+
+/// Write every slice, with the failing slice index and bytes written so far as error context
+pub(super) fn write_all_vectored<W>(w: &mut W, slices: &[IoSlice<'_>]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut bufs = slices.to_vec();
+    let mut remaining = &mut bufs[..];
+    IoSlice::advance_slices(&mut remaining, 0);
+
+    let mut written = 0;
+    while !remaining.is_empty() {
+        match w.write_vectored(remaining) {
+            Ok(0) => {
+                return Err(std::io::Error::new(
+                    ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+                .with_context(|| slice_context(slices, written));
+            }
+            Ok(n) => {
+                written += n;
+                IoSlice::advance_slices(&mut remaining, n);
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e).with_context(|| slice_context(slices, written)),
+        }
+    }
+    Ok(())
+}
+
+fn slice_context(slices: &[IoSlice<'_>], written: usize) -> String {
+    let mut end = 0;
+    let index = slices
+        .iter()
+        .position(|s| {
+            end += s.len();
+            end > written
+        })
+        .unwrap_or(slices.len());
+    value_context(
+        "slice index",
+        format_args!("{index} after {written} bytes written"),
+    )
+}
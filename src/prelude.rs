@@ -0,0 +1,26 @@
+//! Bring all extension traits into scope with a single import
+//!
+//! ```
+//! use anyhow_std::prelude::*;
+//! use std::path::Path;
+//! use std::process::Command;
+//!
+//! let path = Path::new("/tmp/no-extension");
+//! assert!(path.extension_anyhow().is_err());
+//! assert!(Command::new("/! we assume this program does not exist !/").status_anyhow().is_err());
+//! ```
+//!
+//! The [fs] and [process] submodules provide narrower groups of traits.
+
+pub use self::fs::*;
+pub use self::process::*;
+
+/// Extension traits for filesystem paths and the OS strings they contain
+pub mod fs {
+    pub use crate::{DirEntryAnyhow, OpenOptionsAnyhow, OsStrAnyhow, PathAnyhow};
+}
+
+/// Extension traits for [std::process]
+pub mod process {
+    pub use crate::CommandAnyhow;
+}
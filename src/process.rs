@@ -2,13 +2,26 @@
 
 mod child;
 mod command;
+mod editor;
+mod envpolicy;
 mod exitstatus;
 mod output;
+#[cfg(target_family = "unix")]
+mod preexec;
+#[cfg(all(feature = "rlimit", target_family = "unix"))]
+mod rlimit;
+mod stderrcontext;
+mod streaming;
 
 pub use self::child::Child;
-pub use self::command::CommandAnyhow;
+pub(crate) use self::command::SpawnContext;
+pub use self::command::{command_anyhow, CommandAnyhow};
+pub use self::editor::edit_in_editor_anyhow;
+pub use self::envpolicy::EnvPolicy;
 pub use self::exitstatus::ExitStatus;
 pub use self::output::Output;
+pub use self::stderrcontext::{set_stderr_context, stderr_context, StderrContext};
+pub use self::streaming::OutputStream;
 
 #[cfg(test)]
 mod tests;
@@ -1,7 +1,62 @@
-use crate::process::{Child, ExitStatus, Output};
+use crate::process::{Child, EnvPolicy, ExitStatus, Output, OutputStream};
+use crate::{escape_os, PathAnyhow};
 use anyhow::Context;
+use std::ffi::{OsStr, OsString};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Build a [Command] for `program` with `args`, verifying that `program` can be executed
+///
+/// A `program` containing a path separator must be an executable file; otherwise it must be
+/// found as an executable file in a `PATH` directory, as when spawning. Checking eagerly reports
+/// a mistyped program where the command is built rather than where it is later run, and the
+/// [Command] still resolves `program` itself when spawned.
+pub fn command_anyhow<P, I, A>(program: P, args: I) -> anyhow::Result<Command>
+where
+    P: AsRef<OsStr>,
+    I: IntoIterator<Item = A>,
+    A: AsRef<OsStr>,
+{
+    let program = program.as_ref();
+    resolve_program(program)
+        .with_context(|| format!("while validating command program {}", escape_os(program)))?;
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    Ok(cmd)
+}
+
+fn resolve_program(program: &OsStr) -> anyhow::Result<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return check_executable(path).map(|()| path.to_path_buf());
+    }
+
+    let dirs = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&dirs)
+        .map(|dir| dir.join(path))
+        .find(|candidate| check_executable(candidate).is_ok())
+        .ok_or_else(|| anyhow::anyhow!("not found in PATH"))
+}
+
+fn check_executable(path: &Path) -> anyhow::Result<()> {
+    let md = path.metadata_anyhow()?;
+    if !md.is_file() {
+        anyhow::bail!("not a file");
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = md.permissions().mode();
+        if mode & 0o111 == 0 {
+            anyhow::bail!("not executable (mode {:#o})", mode & 0o7777);
+        }
+    }
+    Ok(())
+}
+
 /// Extend [std::process::Command] with [anyhow] methods
 pub trait CommandAnyhow {
     /// Wrap [Command::spawn](std::process::Command::spawn), providing the command as error context
@@ -13,30 +68,234 @@ pub trait CommandAnyhow {
     /// Wrap [Command::status](std::process::Command::status), providing the command as error context
     fn status_anyhow(&mut self) -> anyhow::Result<ExitStatus>;
 
+    /// Run the command, passing each line of its stdout and stderr to `on_line` as it arrives
+    ///
+    /// Both streams are also captured into the returned [Output]. Lines are passed without their
+    /// line ending; one which is not valid UTF-8 stops the run with its stream and line number as
+    /// context, e.g. `with stream stdout line 3`. An unsuccessful exit status is an error whose
+    /// context includes the captured stderr, rendered as chosen with
+    /// [set_stderr_context](crate::process::set_stderr_context).
+    fn run_streaming_anyhow<F>(&mut self, on_line: F) -> anyhow::Result<Output>
+    where
+        F: FnMut(OutputStream, &str);
+
     /// Describe the command for error contexts
+    ///
+    /// The program and arguments are rendered with [escape_os], preceded by any working directory
+    /// and environment variables set on the command, for example
+    /// `command: cd "/tmp" && LANG="C" "ls" "-l"`. Removed variables are shown as `env -u KEY`.
+    /// [Command] does not reveal whether [Command::env_clear] was called, so a cleared
+    /// environment, as by [EnvPolicy::Clear], is not shown.
     fn anyhow_context(&self) -> String;
+
+    /// Choose which environment variables the child inherits
+    ///
+    /// Variables set with [Command::env] afterwards are passed regardless. [EnvPolicy::Clear]
+    /// calls [Command::env_clear], so variables the process sets later are not inherited either.
+    /// [EnvPolicy::Inherit] leaves the command unchanged.
+    fn env_policy(&mut self, policy: EnvPolicy) -> &mut Self;
+
+    /// Limit the child's address space to `bytes`, by setting `RLIMIT_AS` before it executes
+    ///
+    /// Both the soft and the hard limit are lowered, so the child cannot raise them again. It is
+    /// an error, naming the limit and value, for `bytes` to exceed the current hard limit. This
+    /// requires the `rlimit` feature.
+    #[cfg(all(feature = "rlimit", target_family = "unix"))]
+    fn limit_memory_anyhow(&mut self, bytes: u64) -> anyhow::Result<&mut Self>;
+
+    /// Limit the child's processor time to `seconds`, by setting `RLIMIT_CPU` as
+    /// [CommandAnyhow::limit_memory_anyhow] does
+    #[cfg(all(feature = "rlimit", target_family = "unix"))]
+    fn limit_cpu_anyhow(&mut self, seconds: u64) -> anyhow::Result<&mut Self>;
+
+    /// Schedule `hook` to run in the child before it executes, as [CommandExt::pre_exec] does,
+    /// naming `label` if it fails
+    ///
+    /// A failing hook makes spawning fail with the context `in pre-exec hook "<label>"` below the
+    /// command. Only the hook's [raw OS error](std::io::Error::raw_os_error) reaches the parent,
+    /// so any other detail of the error is lost, as is a code of 4096 or more. Once more than
+    /// 262144 distinct labels have been used in the process, hooks with new labels fail without
+    /// one.
+    ///
+    /// # Safety
+    ///
+    /// As for [CommandExt::pre_exec], `hook` runs in the forked child and must only perform
+    /// async-signal-safe operations; in particular it must not allocate or take locks.
+    ///
+    /// [CommandExt::pre_exec]: std::os::unix::process::CommandExt::pre_exec
+    #[cfg(target_family = "unix")]
+    unsafe fn pre_exec_anyhow<F>(&mut self, label: &str, hook: F) -> &mut Self
+    where
+        F: FnMut() -> std::io::Result<()> + Send + Sync + 'static;
+
+    /// Append the child's stdout to the log file at `path`, creating it if needed, as `nohup`
+    /// does
+    ///
+    /// The file is opened immediately with [PathAnyhow::append_anyhow], so a failure carries both
+    /// the command and the log file path as context.
+    fn stdout_to_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>;
+
+    /// Append the child's stderr to the log file at `path`, as
+    /// [CommandAnyhow::stdout_to_anyhow] does for stdout
+    ///
+    /// Passing the same path to both keeps the two streams in one log.
+    fn stderr_to_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>;
 }
 
 impl CommandAnyhow for Command {
     fn spawn_anyhow(&mut self) -> anyhow::Result<Child> {
         self.spawn()
             .map(|c| Child::from((c, self.anyhow_context())))
-            .context(self.anyhow_context())
+            .map_err(|e| spawn_error(self, e))
     }
 
     fn output_anyhow(&mut self) -> anyhow::Result<Output> {
         self.output()
             .map(|o| Output::wrap(o, self.anyhow_context()))
-            .context(self.anyhow_context())
+            .map_err(|e| spawn_error(self, e))
     }
 
     fn status_anyhow(&mut self) -> anyhow::Result<ExitStatus> {
         self.status()
             .map(|c| ExitStatus::from((c, self.anyhow_context())))
-            .context(self.anyhow_context())
+            .map_err(|e| spawn_error(self, e))
+    }
+
+    fn run_streaming_anyhow<F>(&mut self, on_line: F) -> anyhow::Result<Output>
+    where
+        F: FnMut(OutputStream, &str),
+    {
+        super::streaming::run_streaming(self, on_line)
     }
 
     fn anyhow_context(&self) -> String {
-        format!("command: {:?}", self)
+        let mut desc = "command: ".to_string();
+        if let Some(dir) = self.get_current_dir() {
+            desc.push_str(&format!("cd {} && ", escape_os(dir)));
+        }
+        let removed: Vec<&OsStr> = self
+            .get_envs()
+            .filter_map(|(key, value)| value.is_none().then_some(key))
+            .collect();
+        if !removed.is_empty() {
+            desc.push_str("env ");
+            for key in removed {
+                desc.push_str(&format!("-u {} ", key.to_string_lossy()));
+            }
+        }
+        for (key, value) in self.get_envs() {
+            if let Some(value) = value {
+                desc.push_str(&format!("{}={} ", key.to_string_lossy(), escape_os(value)));
+            }
+        }
+        desc.push_str(&escape_os(self.get_program()));
+        for arg in self.get_args() {
+            desc.push(' ');
+            desc.push_str(&escape_os(arg));
+        }
+        desc
+    }
+
+    fn env_policy(&mut self, policy: EnvPolicy) -> &mut Self {
+        match policy {
+            EnvPolicy::Clear => {
+                self.env_clear();
+            }
+            EnvPolicy::Inherit => {}
+            EnvPolicy::InheritExcept(keys) => {
+                for key in keys {
+                    self.env_remove(key);
+                }
+            }
+        }
+        self
+    }
+
+    #[cfg(all(feature = "rlimit", target_family = "unix"))]
+    fn limit_memory_anyhow(&mut self, bytes: u64) -> anyhow::Result<&mut Self> {
+        use super::rlimit::{set_limit, Limit};
+
+        set_limit(self, Limit::Memory, bytes)?;
+        Ok(self)
+    }
+
+    #[cfg(all(feature = "rlimit", target_family = "unix"))]
+    fn limit_cpu_anyhow(&mut self, seconds: u64) -> anyhow::Result<&mut Self> {
+        use super::rlimit::{set_limit, Limit};
+
+        set_limit(self, Limit::Cpu, seconds)?;
+        Ok(self)
+    }
+
+    #[cfg(target_family = "unix")]
+    unsafe fn pre_exec_anyhow<F>(&mut self, label: &str, hook: F) -> &mut Self
+    where
+        F: FnMut() -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        super::preexec::pre_exec(self, label, hook);
+        self
+    }
+
+    fn stdout_to_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = open_log(self, path.as_ref())?;
+        Ok(self.stdout(file))
+    }
+
+    fn stderr_to_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = open_log(self, path.as_ref())?;
+        Ok(self.stderr(file))
+    }
+}
+
+/// Open the log file at `path` for appending the output of `cmd`
+fn open_log(cmd: &Command, path: &Path) -> anyhow::Result<std::fs::File> {
+    path.append_anyhow().with_context(|| cmd.anyhow_context())
+}
+
+/// The command context of a failed spawn, rendered as [CommandAnyhow::anyhow_context]
+#[derive(Debug)]
+pub(crate) struct SpawnContext {
+    desc: String,
+    missing_program: Option<OsString>,
+}
+
+impl SpawnContext {
+    /// The program, if spawning failed because it was not found
+    pub(crate) fn missing_program(&self) -> Option<&OsStr> {
+        self.missing_program.as_deref()
     }
 }
+
+impl std::fmt::Display for SpawnContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.desc)
+    }
+}
+
+/// Convert a spawn error of `cmd`, attributing it to a failed pre-exec hook where possible
+fn spawn_error(cmd: &Command, e: std::io::Error) -> anyhow::Error {
+    // A missing working directory is also reported as `NotFound`.
+    let missing_program = (e.kind() == ErrorKind::NotFound
+        && cmd.get_current_dir().is_none_or(|dir| dir.is_dir()))
+    .then(|| cmd.get_program().to_os_string());
+    let ctx = SpawnContext {
+        desc: cmd.anyhow_context(),
+        missing_program,
+    };
+
+    #[cfg(target_family = "unix")]
+    let e = super::preexec::decode(e);
+    #[cfg(not(target_family = "unix"))]
+    let e = anyhow::Error::from(e);
+    e.context(ctx)
+}
@@ -0,0 +1,57 @@
+use crate::context::render_path;
+use crate::process::CommandAnyhow;
+use crate::PathAnyhow;
+use anyhow::Context;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Let the user edit `initial` in their editor and return the edited text
+///
+/// The text is written to a new temporary file, the editor named by `$VISUAL` or `$EDITOR`
+/// (falling back to `vi`) is run on it and waited for, and the file is read back and removed.
+/// The editor value is split on whitespace, so it may include arguments such as `code --wait`.
+/// Errors name the failing phase, the editor, and the temporary path; the file is kept if the
+/// editor fails so edits are not lost.
+pub fn edit_in_editor_anyhow(initial: &str) -> anyhow::Result<String> {
+    edit_with(initial, |key| crate::env::var(key).ok())
+}
+
+/// Edit `initial` as [edit_in_editor_anyhow] does, looking the editor variables up with `lookup`
+/// rather than in the environment
+pub(super) fn edit_with<F>(initial: &str, lookup: F) -> anyhow::Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let editor = lookup("VISUAL")
+        .or_else(|| lookup("EDITOR"))
+        .unwrap_or_else(|| "vi".to_string());
+    let path = create_temp_file(initial).context("while creating the file to edit")?;
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status_anyhow()
+        .and_then(|status| status.exit_ok())
+        .with_context(|| {
+            format!(
+                "while running editor {:?} on {}",
+                editor,
+                render_path(&path)
+            )
+        })?;
+
+    let edited = path
+        .read_to_string_anyhow()
+        .context("while reading the edited file")?;
+    path.remove_file_anyhow()
+        .context("while removing the edited file")?;
+    Ok(edited)
+}
+
+fn create_temp_file(contents: &str) -> anyhow::Result<PathBuf> {
+    let path = std::env::temp_dir().unique_path_anyhow("anyhow-std-edit-", ".txt")?;
+    path.write_anyhow(contents)?;
+    Ok(path)
+}
@@ -0,0 +1,14 @@
+use std::ffi::OsString;
+
+/// Which environment variables a child inherits, set with
+/// [CommandAnyhow::env_policy](crate::process::CommandAnyhow::env_policy)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EnvPolicy {
+    /// Start from an empty environment
+    Clear,
+    /// Inherit the whole environment, as commands do by default
+    #[default]
+    Inherit,
+    /// Inherit every variable except the given keys
+    InheritExcept(Vec<OsString>),
+}
@@ -0,0 +1,69 @@
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// A child only reports a failed hook to the parent as a raw OS error code, so failures are
+/// encoded as this marker bit, the hook's index in [LABELS], and the original code.
+///
+/// Codes that do not fit in [ERRNO_BITS] are reported as failures without a code; Linux error
+/// numbers are all below 4096.
+const MARKER: i32 = 0x4000_0000;
+const ERRNO_BITS: u32 = 12;
+const ERRNO_MASK: i32 = (1 << ERRNO_BITS) - 1;
+const MAX_HOOKS: usize = 1 << (30 - ERRNO_BITS);
+
+/// The distinct labels registered so far, so commands built repeatedly with the same labels, as a
+/// supervisor does, do not grow it
+static LABELS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Register `hook` on `cmd`, so that its failure can be traced back to `label`
+///
+/// # Safety
+///
+/// As for [CommandExt::pre_exec].
+pub(super) unsafe fn pre_exec<F>(cmd: &mut Command, label: &str, mut hook: F)
+where
+    F: FnMut() -> io::Result<()> + Send + Sync + 'static,
+{
+    let id = {
+        let mut labels = LABELS.lock().unwrap_or_else(|e| e.into_inner());
+        match labels.iter().position(|l| l == label) {
+            Some(id) => id,
+            None => {
+                labels.push(label.to_string());
+                labels.len() - 1
+            }
+        }
+    };
+
+    let encode = move |e: io::Error| {
+        if id >= MAX_HOOKS {
+            return e;
+        }
+        let errno = e
+            .raw_os_error()
+            .filter(|n| *n & !ERRNO_MASK == 0)
+            .unwrap_or(0);
+        io::Error::from_raw_os_error(MARKER | (id as i32) << ERRNO_BITS | errno)
+    };
+    cmd.pre_exec(move || hook().map_err(encode));
+}
+
+/// Restore the original error of a failed hook, with its label as context
+pub(crate) fn decode(e: io::Error) -> anyhow::Error {
+    let Some(code) = e.raw_os_error().filter(|n| n & MARKER != 0) else {
+        return e.into();
+    };
+    let id = ((code & !MARKER) >> ERRNO_BITS) as usize;
+    let labels = LABELS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(label) = labels.get(id) else {
+        return e.into();
+    };
+
+    let original = match code & ERRNO_MASK {
+        0 => io::Error::other("the hook failed without an OS error code"),
+        errno => io::Error::from_raw_os_error(errno),
+    };
+    anyhow::Error::new(original).context(format!("in pre-exec hook {:?}", label))
+}
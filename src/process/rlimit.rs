@@ -0,0 +1,81 @@
+use crate::context::value_context;
+use crate::process::CommandAnyhow;
+use anyhow::Context;
+use std::process::Command;
+
+/// A resource limit settable with [CommandAnyhow]
+#[derive(Copy, Clone, Debug)]
+pub(super) enum Limit {
+    /// `RLIMIT_AS`, the address space in bytes
+    Memory,
+    /// `RLIMIT_CPU`, the processor time in seconds
+    Cpu,
+}
+
+impl Limit {
+    fn name(self) -> &'static str {
+        match self {
+            Limit::Memory => "RLIMIT_AS",
+            Limit::Cpu => "RLIMIT_CPU",
+        }
+    }
+
+    fn get(self) -> std::io::Result<libc::rlimit> {
+        let mut lim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ret = match self {
+            Limit::Memory => unsafe { libc::getrlimit(libc::RLIMIT_AS, &mut lim) },
+            Limit::Cpu => unsafe { libc::getrlimit(libc::RLIMIT_CPU, &mut lim) },
+        };
+        check(ret).map(|()| lim)
+    }
+
+    fn set(self, lim: &libc::rlimit) -> std::io::Result<()> {
+        check(match self {
+            Limit::Memory => unsafe { libc::setrlimit(libc::RLIMIT_AS, lim) },
+            Limit::Cpu => unsafe { libc::setrlimit(libc::RLIMIT_CPU, lim) },
+        })
+    }
+}
+
+/// Make `cmd` lower both the soft and hard `limit` to `value` in the child before it executes
+///
+/// The value is checked against the current hard limit here, since an error in the child only
+/// reaches the parent as a bare OS error, labelled with the limit.
+pub(super) fn set_limit(cmd: &mut Command, limit: Limit, value: u64) -> anyhow::Result<()> {
+    let desc = format!("{}={value}", limit.name());
+    let value = check_limit(limit, value)
+        .with_context(|| value_context("resource limit", &desc))
+        .with_context(|| cmd.anyhow_context())?;
+
+    let lim = libc::rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    };
+    // SAFETY: `setrlimit` is async-signal-safe, and the closure neither allocates nor locks.
+    unsafe {
+        super::preexec::pre_exec(cmd, &format!("set resource limit {desc}"), move || {
+            limit.set(&lim)
+        });
+    }
+    Ok(())
+}
+
+fn check_limit(limit: Limit, value: u64) -> anyhow::Result<libc::rlim_t> {
+    let value = libc::rlim_t::try_from(value).context("value out of range")?;
+    let current = limit.get()?;
+    if current.rlim_max != libc::RLIM_INFINITY && value > current.rlim_max {
+        anyhow::bail!("exceeds the hard limit {}", current.rlim_max);
+    }
+    Ok(value)
+}
+
+fn check(ret: libc::c_int) -> std::io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::context::value_context;
+use std::sync::RwLock;
+
+static STDERR_CONTEXT: RwLock<StderrContext> = RwLock::new(StderrContext::new());
+
+/// How captured child stderr is rendered into error contexts, as by
+/// [CommandAnyhow::run_streaming_anyhow](crate::process::CommandAnyhow::run_streaming_anyhow)
+///
+/// By default stderr is embedded whole and unchanged. Colorful or verbose tools may otherwise
+/// corrupt terminals and logs showing the error chain, so their escape sequences can be stripped
+/// and the output cut down to its end, where the cause of a failure usually is.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StderrContext {
+    strip_ansi: bool,
+    max_lines: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl StderrContext {
+    /// Embed stderr whole and unchanged
+    pub const fn new() -> Self {
+        StderrContext {
+            strip_ansi: false,
+            max_lines: None,
+            max_bytes: None,
+        }
+    }
+
+    /// Remove ANSI escape sequences, such as color codes
+    pub fn strip_ansi(mut self, enabled: bool) -> Self {
+        self.strip_ansi = enabled;
+        self
+    }
+
+    /// Keep only the last `lines` lines
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = Some(lines);
+        self
+    }
+
+    /// Keep only the last `bytes` bytes, after any line limit
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Render `stderr` as the context `with stderr "<text>"`, noting any truncation, e.g.
+    /// `with stderr (last 2 of 5 lines) "<text>"`
+    pub fn render(&self, stderr: &[u8]) -> String {
+        let lossy = String::from_utf8_lossy(stderr);
+        let mut text = if self.strip_ansi {
+            strip_ansi(&lossy)
+        } else {
+            lossy.into_owned()
+        };
+
+        let mut notes = vec![];
+        if let Some(max) = self.max_lines {
+            let total = text.lines().count();
+            if total > max {
+                let start: usize = text
+                    .split_inclusive('\n')
+                    .take(total - max)
+                    .map(str::len)
+                    .sum();
+                text.drain(..start);
+                notes.push(format!("last {max} of {total} lines"));
+            }
+        }
+        if let Some(max) = self.max_bytes {
+            if text.len() > max {
+                let mut start = text.len() - max;
+                while !text.is_char_boundary(start) {
+                    start += 1;
+                }
+                let total = text.len();
+                text.drain(..start);
+                notes.push(format!("last {} of {total} bytes", text.len()));
+            }
+        }
+
+        let name = if notes.is_empty() {
+            "stderr".to_string()
+        } else {
+            format!("stderr ({})", notes.join(", "))
+        };
+        value_context(&name, format_args!("{text:?}"))
+    }
+}
+
+/// Render stderr for error contexts as configured with [set_stderr_context]
+pub(crate) fn render(stderr: &[u8]) -> String {
+    STDERR_CONTEXT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .render(stderr)
+}
+
+/// Choose how captured stderr is rendered into error contexts, process-wide, returning the
+/// previous setting
+pub fn set_stderr_context(options: StderrContext) -> StderrContext {
+    let mut current = STDERR_CONTEXT.write().unwrap_or_else(|e| e.into_inner());
+    std::mem::replace(&mut *current, options)
+}
+
+/// The setting chosen with [set_stderr_context]
+pub fn stderr_context() -> StderrContext {
+    *STDERR_CONTEXT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Remove CSI sequences such as `ESC [ 31 m`, OSC sequences such as terminal titles, and other
+/// two-character escapes
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                // Parameters and intermediates, then one final byte in `@`..=`~`.
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                // Terminated by BEL or by `ESC \`.
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
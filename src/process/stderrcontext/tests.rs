@@ -0,0 +1,31 @@
+use crate::process::StderrContext;
+use test_case::test_case;
+
+#[test_case(StderrContext::new() => r#"with stderr "\u{1b}[31merror\u{1b}[0m: a\nb\nc\n""#; "unchanged")]
+#[test_case(
+    StderrContext::new().strip_ansi(true)
+    => r#"with stderr "error: a\nb\nc\n""#
+    ; "stripped"
+)]
+#[test_case(
+    StderrContext::new().strip_ansi(true).max_lines(2)
+    => r#"with stderr (last 2 of 3 lines) "b\nc\n""#
+    ; "lines"
+)]
+#[test_case(
+    StderrContext::new().strip_ansi(true).max_bytes(3)
+    => r#"with stderr (last 3 of 13 bytes) "\nc\n""#
+    ; "bytes"
+)]
+fn render(options: StderrContext) -> String {
+    options.render(b"\x1b[31merror\x1b[0m: a\nb\nc\n")
+}
+
+#[test]
+fn strip_osc() {
+    let options = StderrContext::new().strip_ansi(true);
+    assert_eq!(
+        options.render(b"\x1b]0;title\x07done\x1b]8;;http://x\x1b\\link"),
+        r#"with stderr "donelink""#
+    );
+}
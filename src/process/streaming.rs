@@ -0,0 +1,114 @@
+use crate::context::value_context;
+use crate::process::{CommandAnyhow, Output};
+use anyhow::Context;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+/// A standard output stream of a child, as passed to
+/// [CommandAnyhow::run_streaming_anyhow]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    /// The child's standard output
+    Stdout,
+    /// The child's standard error
+    Stderr,
+}
+
+impl fmt::Display for OutputStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        })
+    }
+}
+
+type Line = (OutputStream, std::io::Result<Vec<u8>>);
+
+pub(super) fn run_streaming<F>(cmd: &mut Command, mut on_line: F) -> anyhow::Result<Output>
+where
+    F: FnMut(OutputStream, &str),
+{
+    let cmddesc = cmd.anyhow_context();
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_anyhow()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut readers = vec![];
+    if let Some(out) = child.stdout.take() {
+        readers.push(read_lines(OutputStream::Stdout, out, tx.clone()));
+    }
+    if let Some(err) = child.stderr.take() {
+        readers.push(read_lines(OutputStream::Stderr, err, tx.clone()));
+    }
+    drop(tx);
+
+    let mut stdout = vec![];
+    let mut stderr = vec![];
+    let mut counts = [0usize; 2];
+    let res: anyhow::Result<()> = rx.into_iter().try_for_each(|(stream, line)| {
+        let (captured, count) = match stream {
+            OutputStream::Stdout => (&mut stdout, &mut counts[0]),
+            OutputStream::Stderr => (&mut stderr, &mut counts[1]),
+        };
+        *count += 1;
+        let at = || value_context("stream", format_args!("{stream} line {count}"));
+        let line = line.with_context(at)?;
+        captured.extend_from_slice(&line);
+        let text = std::str::from_utf8(&line).with_context(at)?;
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        on_line(stream, text.strip_suffix('\r').unwrap_or(text));
+        Ok(())
+    });
+    if let Err(e) = res {
+        // The receiver is gone, so the readers stop at their next line.
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e.context(cmddesc));
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    // Unlike the errors above, this already has the command as context, from [Child::wait].
+    let status = child.wait()?;
+    if let Err(e) = status.exit_ok() {
+        return Err(e)
+            .context(super::stderrcontext::render(&stderr))
+            .context(cmddesc);
+    }
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+fn read_lines<R>(
+    stream: OutputStream,
+    reader: R,
+    tx: mpsc::Sender<Line>,
+) -> std::thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut line = vec![];
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                res => {
+                    let failed = res.is_err();
+                    if tx.send((stream, res.map(|_| line))).is_err() || failed {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
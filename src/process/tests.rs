@@ -55,3 +55,231 @@ fn kill_twice() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(target_family = "unix")]
+#[test]
+fn context_escapes_arguments() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut cmd = Command::new("prog");
+    cmd.arg(std::ffi::OsStr::from_bytes(b"\x81\xFF"))
+        .arg("new\nline")
+        .env("LANG", "C")
+        .current_dir("/tmp");
+    assert_eq!(
+        cmd.anyhow_context(),
+        r#"command: cd "/tmp" && LANG="C" "prog" "\x81\xFF" "new\nline""#
+    );
+}
+
+#[test]
+fn run_streaming() -> anyhow::Result<()> {
+    use crate::process::OutputStream;
+
+    let mut lines = vec![];
+    let output = Command::new("sh")
+        .args([
+            "-c",
+            "echo one; echo warn >&2; printf 'two\\r\\nthree\\r\\r\\nfour'",
+        ])
+        .run_streaming_anyhow(|stream, line| lines.push((stream, line.to_string())))?;
+    let stdout_lines: Vec<_> = lines
+        .iter()
+        .filter(|(s, _)| *s == OutputStream::Stdout)
+        .map(|(_, l)| l.as_str())
+        .collect();
+    assert_eq!(stdout_lines, ["one", "two", "three\r", "four"]);
+    assert!(lines.contains(&(OutputStream::Stderr, "warn".to_string())));
+    assert_eq!(output.stdout, b"one\ntwo\r\nthree\r\r\nfour");
+    assert_eq!(output.stderr, b"warn\n");
+    Ok(())
+}
+
+#[test_case(
+    "echo ok; echo boom >&2; exit 3"
+    => r#"command: "sh" "-c" "echo ok; echo boom >&2; exit 3": with stderr "boom\n": status: 3: error exit status"#
+    ; "failure"
+)]
+#[test_case(
+    "echo ok; printf '\\377\\n'"
+    => r#"command: "sh" "-c" "echo ok; printf '\\377\\n'": with stream stdout line 2: invalid utf-8 sequence of 1 bytes from index 0"#
+    ; "invalid utf8"
+)]
+fn run_streaming_err(script: &str) -> String {
+    let err = Command::new("sh")
+        .args(["-c", script])
+        .run_streaming_anyhow(|_, _| {})
+        .err()
+        .unwrap();
+    format!("{:#}", err)
+}
+
+#[test]
+fn env_policy() -> anyhow::Result<()> {
+    use crate::process::EnvPolicy;
+
+    let mut cmd = Command::new("/usr/bin/env");
+    cmd.env_policy(EnvPolicy::Clear).env("A", "1");
+    assert_eq!(cmd.anyhow_context(), r#"command: A="1" "/usr/bin/env""#);
+    let stdout = String::from_utf8(cmd.output_anyhow()?.stdout)?;
+    assert!(stdout.lines().any(|l| l == "A=1"), "{stdout}");
+    assert!(!stdout.lines().any(|l| l.starts_with("PATH=")), "{stdout}");
+
+    let mut cmd = Command::new("prog");
+    cmd.env_policy(EnvPolicy::InheritExcept(vec!["HOME".into()]));
+    assert_eq!(cmd.anyhow_context(), r#"command: env -u HOME "prog""#);
+    Ok(())
+}
+
+#[test]
+fn command_anyhow_from_path() -> anyhow::Result<()> {
+    let mut cmd = crate::process::command_anyhow("true", ["ignored"])?;
+    assert_eq!(cmd.anyhow_context(), r#"command: "true" "ignored""#);
+    cmd.status_anyhow()?.exit_ok()
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn command_anyhow_invalid() {
+    use crate::testutils::stringify_error;
+    use crate::PathAnyhow;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let script = dir.path().join("script");
+    script.write_anyhow("#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&script, std::os::unix::fs::PermissionsExt::from_mode(0o644)).unwrap();
+    let dir_str = dir.path().display().to_string();
+    let check = |program: &std::ffi::OsStr| {
+        stringify_error(crate::process::command_anyhow(program, Vec::<&str>::new()))
+            .map(|_| ())
+            .map_err(|e| e.replace(&dir_str, "DIR"))
+    };
+
+    assert_eq!(
+        check(script.as_os_str()),
+        Err(
+            r#"while validating command program "DIR/script": not executable (mode 0o644)"#
+                .to_string()
+        )
+    );
+    assert_eq!(
+        check(dir.path().join("missing").as_os_str()),
+        Err(r#"while validating command program "DIR/missing": while processing path "DIR/missing": No such file or directory (os error 2)"#.to_string())
+    );
+    assert_eq!(
+        check("! we assume this program does not exist !".as_ref()),
+        Err(r#"while validating command program "! we assume this program does not exist !": not found in PATH"#.to_string())
+    );
+}
+
+#[test]
+fn output_to_log_files() -> anyhow::Result<()> {
+    use crate::PathAnyhow;
+
+    let dir = tempfile::TempDir::new()?;
+    let log = dir.path().join("log");
+    log.write_anyhow("earlier\n")?;
+    Command::new("sh")
+        .args(["-c", "echo out; echo err >&2"])
+        .stdout_to_anyhow(&log)?
+        .stderr_to_anyhow(&log)?
+        .status_anyhow()?
+        .exit_ok()?;
+    assert_eq!(log.read_to_string_anyhow()?, "earlier\nout\nerr\n");
+
+    let missing = dir.path().join("missing").join("log");
+    let err = Command::new("true")
+        .stdout_to_anyhow(&missing)
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", err).replace(&dir.path().display().to_string(), "DIR"),
+        r#"command: "true": while processing path "DIR/missing/log": No such file or directory (os error 2)"#,
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn edit_in_editor() -> anyhow::Result<()> {
+    use super::editor::edit_with;
+
+    let editor = |visual: Option<&str>, editor: Option<&str>| {
+        let (visual, editor) = (visual.map(String::from), editor.map(String::from));
+        move |key: &str| match key {
+            "VISUAL" => visual.clone(),
+            "EDITOR" => editor.clone(),
+            _ => None,
+        }
+    };
+    let edited = edit_with(
+        "hello world\n",
+        editor(Some("sed -i s/hello/edited/"), Some("false")),
+    )?;
+    assert_eq!(edited, "edited world\n");
+    let edited = edit_with("hello", editor(None, Some("sed -i s/hello/edited/")))?;
+    assert_eq!(edited, "edited");
+
+    let err = edit_with("kept", editor(Some("false"), None))
+        .err()
+        .unwrap();
+    let msg = format!("{:#}", err);
+    assert!(
+        msg.starts_with(r#"while running editor "false" on ""#),
+        "{}",
+        msg
+    );
+    assert!(msg.ends_with("status: 1: error exit status"), "{}", msg);
+
+    // The temporary file is kept when the editor fails.
+    let path = msg.split('"').nth(3).unwrap();
+    assert_eq!(std::fs::read_to_string(path)?, "kept");
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn pre_exec_hook_failure() -> anyhow::Result<()> {
+    let mut cmd = Command::new("true");
+    unsafe {
+        cmd.pre_exec_anyhow("succeeds", || Ok(()))
+            .pre_exec_anyhow("drop privileges", || {
+                Err(std::io::Error::from_raw_os_error(1))
+            });
+    }
+    assert_eq!(
+        format!("{:#}", cmd.status_anyhow().err().unwrap()),
+        r#"command: "true": in pre-exec hook "drop privileges": Operation not permitted (os error 1)"#,
+    );
+
+    // Reused labels are interned, and codes beyond the encoding are reported without one.
+    let mut cmd = Command::new("true");
+    unsafe {
+        cmd.pre_exec_anyhow("drop privileges", || Ok(()))
+            .pre_exec_anyhow("succeeds", || Err(std::io::Error::from_raw_os_error(4096)));
+    }
+    assert_eq!(
+        format!("{:#}", cmd.status_anyhow().err().unwrap()),
+        r#"command: "true": in pre-exec hook "succeeds": the hook failed without an OS error code"#,
+    );
+
+    let mut cmd = Command::new("true");
+    unsafe {
+        cmd.pre_exec_anyhow("succeeds", || Ok(()));
+    }
+    cmd.status_anyhow()?.exit_ok()
+}
+
+#[cfg(all(feature = "rlimit", target_family = "unix"))]
+#[test]
+fn limits() -> anyhow::Result<()> {
+    let output = Command::new("sh")
+        .args(["-c", "ulimit -v; ulimit -t"])
+        .limit_memory_anyhow(1 << 30)?
+        .limit_cpu_anyhow(60)?
+        .output_anyhow()?;
+    output.status.exit_ok()?;
+    assert_eq!(String::from_utf8(output.stdout)?, "1048576\n60\n");
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Multi-line rendering of [anyhow::Error] chains for command line tools
+//!
+//! [render_chain] formats each error in the chain on its own line, indented beneath the context it
+//! causes. An [ErrorCollection] in the chain is expanded into one sub-tree per collected error:
+//!
+//! ```
+//! use anyhow_std::PathAnyhow;
+//! use std::path::Path;
+//!
+//! let err = Path::new("/this/path/should/not/exist")
+//!     .strip_prefix_anyhow("/other")
+//!     .err()
+//!     .unwrap();
+//! assert_eq!(
+//!     anyhow_std::report::render_chain(&err),
+//!     [
+//!         r#"while processing path "/this/path/should/not/exist""#,
+//!         r#"  caused by: with prefix "/other""#,
+//!         r#"    caused by: prefix not found"#,
+//!     ]
+//!     .join("\n"),
+//! );
+//! ```
+//!
+//! With the `color` feature, `RenderOptions::color` highlights quoted paths and values and dims
+//! error kinds with ANSI escapes.
+
+use crate::ErrorCollection;
+use std::error::Error;
+use std::fmt::Write;
+
+mod exitcode;
+mod hints;
+
+pub use self::exitcode::ExitCodePolicy;
+
+/// Render the chain of `err` with the default [RenderOptions]
+pub fn render_chain(err: &anyhow::Error) -> String {
+    RenderOptions::default().render(err)
+}
+
+/// Options for rendering error chains, which are all disabled by default
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    kinds: bool,
+    dedupe: bool,
+    hints: bool,
+    #[cfg(feature = "color")]
+    color: bool,
+}
+
+const QUOTED_STYLE: &str = "\x1b[1;36m";
+const KIND_STYLE: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+impl RenderOptions {
+    /// Append the [std::io::ErrorKind] of each [std::io::Error] in the chain, as in
+    /// `No such file or directory (os error 2) [NotFound]`
+    pub fn kinds(mut self, enabled: bool) -> Self {
+        self.kinds = enabled;
+        self
+    }
+
+    /// Omit a context identical to one already rendered above it in the same chain
+    ///
+    /// This keeps chains readable when nested helpers each add the same context, such as
+    /// `while processing path "a"`.
+    pub fn dedupe(mut self, enabled: bool) -> Self {
+        self.dedupe = enabled;
+        self
+    }
+
+    /// Append a `hint: ` line suggesting a fix for common failures of the innermost
+    /// [std::io::Error]
+    ///
+    /// For example, `PermissionDenied` suggests checking the ownership and permissions of the
+    /// path being processed, and `NotFound` because a command's program could not be found when
+    /// spawning it asks whether the program is installed and on `PATH`.
+    pub fn hints(mut self, enabled: bool) -> Self {
+        self.hints = enabled;
+        self
+    }
+
+    /// Color the output with ANSI escapes: quoted paths and values are highlighted and error
+    /// kinds are dimmed
+    #[cfg(feature = "color")]
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Enable [RenderOptions::color] if stderr is a terminal and `NO_COLOR` is not set
+    #[cfg(feature = "color")]
+    pub fn color_auto(self) -> Self {
+        use std::io::IsTerminal;
+
+        let enabled = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        self.color(enabled)
+    }
+
+    /// Render the chain of `err` as an indented multi-line report without a trailing newline
+    pub fn render(&self, err: &anyhow::Error) -> String {
+        let mut out = String::new();
+        self.render_chain(&mut out, err.chain(), 0, "");
+        if let Some(hint) = hints::hint(err).filter(|_| self.hints) {
+            out.push_str("\nhint: ");
+            self.write_message(&mut out, &hint);
+        }
+        out
+    }
+
+    fn render_chain<'a, I>(&self, out: &mut String, chain: I, depth: usize, first_prefix: &str)
+    where
+        I: Iterator<Item = &'a (dyn Error + 'static)>,
+    {
+        let mut seen: Vec<String> = vec![];
+        for cause in chain {
+            let msg = cause.to_string();
+            if self.dedupe && seen.contains(&msg) {
+                continue;
+            }
+
+            let i = seen.len();
+            let prefix = if i == 0 { first_prefix } else { "caused by: " };
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            write!(out, "{:indent$}{}", "", prefix, indent = 2 * (depth + i)).unwrap();
+
+            if let Some(errors) = cause.downcast_ref::<ErrorCollection>() {
+                let n = errors.len();
+                write!(out, "{} error{}:", n, if n == 1 { "" } else { "s" }).unwrap();
+                for e in errors {
+                    self.render_chain(out, e.chain(), depth + i + 1, "- ");
+                }
+            } else {
+                self.write_message(out, &msg);
+                if let Some(ioerr) = cause
+                    .downcast_ref::<std::io::Error>()
+                    .filter(|_| self.kinds)
+                {
+                    self.write_kind(out, ioerr.kind());
+                }
+            }
+            seen.push(msg);
+        }
+    }
+
+    fn colored(&self) -> bool {
+        #[cfg(feature = "color")]
+        {
+            self.color
+        }
+
+        #[cfg(not(feature = "color"))]
+        {
+            false
+        }
+    }
+
+    fn write_message(&self, out: &mut String, msg: &str) {
+        if !self.colored() {
+            out.push_str(msg);
+            return;
+        }
+
+        // Quoted segments are paths or values rendered with `escape_os`, so `\"` does not end one.
+        let mut quoted = false;
+        let mut escaped = false;
+        for c in msg.chars() {
+            if c == '"' && !escaped {
+                if !quoted {
+                    out.push_str(QUOTED_STYLE);
+                }
+                out.push(c);
+                if quoted {
+                    out.push_str(RESET);
+                }
+                quoted = !quoted;
+            } else {
+                out.push(c);
+            }
+            escaped = quoted && c == '\\' && !escaped;
+        }
+        if quoted {
+            out.push_str(RESET);
+        }
+    }
+
+    fn write_kind(&self, out: &mut String, kind: std::io::ErrorKind) {
+        if self.colored() {
+            write!(out, " {}[{:?}]{}", KIND_STYLE, kind, RESET).unwrap();
+        } else {
+            write!(out, " [{:?}]", kind).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+use std::io::ErrorKind;
+
+/// A mapping from errors to process exit codes
+///
+/// The code is chosen by the [ErrorKind] of the first [std::io::Error] in the error chain. The
+/// [Default] policy follows the BSD `sysexits.h` conventions:
+///
+/// | Kind | Code |
+/// |------|------|
+/// | [NotFound](ErrorKind::NotFound) | 66 (`EX_NOINPUT`) |
+/// | [PermissionDenied](ErrorKind::PermissionDenied) | 77 (`EX_NOPERM`) |
+/// | [AlreadyExists](ErrorKind::AlreadyExists) | 73 (`EX_CANTCREAT`) |
+/// | [InvalidInput](ErrorKind::InvalidInput), [InvalidData](ErrorKind::InvalidData) | 65 (`EX_DATAERR`) |
+/// | [Unsupported](ErrorKind::Unsupported) | 69 (`EX_UNAVAILABLE`) |
+/// | any other kind | 74 (`EX_IOERR`) |
+/// | no [std::io::Error] in the chain | 1 |
+///
+/// ```
+/// use anyhow_std::report::ExitCodePolicy;
+/// use anyhow_std::PathAnyhow;
+/// use std::io::ErrorKind;
+///
+/// let err = std::path::Path::new("/this/path/should/not/exist")
+///     .read_anyhow()
+///     .err()
+///     .unwrap();
+/// assert_eq!(ExitCodePolicy::default().exit_code(&err), 66);
+///
+/// let policy = ExitCodePolicy::default().with_kind(ErrorKind::NotFound, 2);
+/// assert_eq!(policy.exit_code(&err), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExitCodePolicy {
+    kinds: Vec<(ErrorKind, u8)>,
+    io_default: u8,
+    default: u8,
+}
+
+impl Default for ExitCodePolicy {
+    fn default() -> Self {
+        ExitCodePolicy {
+            kinds: vec![
+                (ErrorKind::NotFound, 66),
+                (ErrorKind::PermissionDenied, 77),
+                (ErrorKind::AlreadyExists, 73),
+                (ErrorKind::InvalidInput, 65),
+                (ErrorKind::InvalidData, 65),
+                (ErrorKind::Unsupported, 69),
+            ],
+            io_default: 74,
+            default: 1,
+        }
+    }
+}
+
+impl ExitCodePolicy {
+    /// Exit with `code` for errors of `kind`, replacing any existing mapping
+    pub fn with_kind(mut self, kind: ErrorKind, code: u8) -> Self {
+        self.kinds.retain(|&(k, _)| k != kind);
+        self.kinds.push((kind, code));
+        self
+    }
+
+    /// Exit with `code` for [std::io::Error] kinds without a mapping
+    pub fn with_io_default(mut self, code: u8) -> Self {
+        self.io_default = code;
+        self
+    }
+
+    /// Exit with `code` for errors without a [std::io::Error] in the chain
+    pub fn with_default(mut self, code: u8) -> Self {
+        self.default = code;
+        self
+    }
+
+    /// The exit code for `err`
+    pub fn exit_code(&self, err: &anyhow::Error) -> u8 {
+        match err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<std::io::Error>())
+        {
+            Some(ioerr) => self
+                .kinds
+                .iter()
+                .find(|&&(k, _)| k == ioerr.kind())
+                .map(|&(_, code)| code)
+                .unwrap_or(self.io_default),
+            None => self.default,
+        }
+    }
+
+    /// Exit the process with the code for `err`, after printing it to stderr
+    pub fn exit(&self, err: &anyhow::Error) -> ! {
+        eprintln!("{:#}", err);
+        std::process::exit(i32::from(self.exit_code(err)))
+    }
+}
@@ -0,0 +1,39 @@
+use crate::context::PathContext;
+use crate::escape_os;
+use crate::process::SpawnContext;
+use std::io::ErrorKind;
+
+/// Suggest a fix for the innermost [std::io::Error] in the chain of `err`, based on its kind and
+/// the path or command contexts above it
+///
+/// Paths come from the outermost [PathContext]; a missing program is named only by the context
+/// of a command which failed to spawn because its program was not found.
+pub(super) fn hint(err: &anyhow::Error) -> Option<String> {
+    let path = err.downcast_ref::<PathContext>().map(|ctx| {
+        ctx.paths()
+            .iter()
+            .map(escape_os)
+            .collect::<Vec<_>>()
+            .join(" and ")
+    });
+    let program = err
+        .downcast_ref::<SpawnContext>()
+        .and_then(SpawnContext::missing_program)
+        .map(escape_os);
+    let kind = err
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .last()
+        .map(std::io::Error::kind);
+
+    match (kind?, path, program) {
+        (ErrorKind::NotFound, _, Some(program)) => {
+            Some(format!("is {} installed and on PATH?", program))
+        }
+        (ErrorKind::PermissionDenied, Some(path), _) => {
+            Some(format!("check ownership and permissions of {}", path))
+        }
+        (ErrorKind::PermissionDenied, None, _) => Some("check ownership and permissions".into()),
+        _ => None,
+    }
+}
@@ -0,0 +1,205 @@
+use crate::report::{render_chain, ExitCodePolicy, RenderOptions};
+use crate::{ErrorCollection, PathAnyhow};
+use anyhow::{anyhow, Context};
+use std::io::ErrorKind;
+use std::path::Path;
+use test_case::test_case;
+
+#[test]
+fn single() {
+    assert_eq!(render_chain(&anyhow!("only")), "only");
+}
+
+#[test]
+fn kinds() {
+    let err = Path::new("/this/path/should/not/exist")
+        .read_anyhow()
+        .context("while loading")
+        .err()
+        .unwrap();
+    assert_eq!(
+        RenderOptions::default().kinds(true).render(&err),
+        [
+            "while loading",
+            r#"  caused by: while processing path "/this/path/should/not/exist""#,
+            "    caused by: No such file or directory (os error 2) [NotFound]",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn collection_tree() {
+    let errors: ErrorCollection = [anyhow!("first cause").context("first"), anyhow!("second")]
+        .into_iter()
+        .collect();
+    let err = errors
+        .into_result(())
+        .context("while scanning")
+        .err()
+        .unwrap();
+    assert_eq!(
+        render_chain(&err),
+        [
+            "while scanning",
+            "  caused by: 2 errors:",
+            "    - first",
+            "      caused by: first cause",
+            "    - second",
+        ]
+        .join("\n")
+    );
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn color() {
+    let err = Path::new("/a \"b\"")
+        .read_anyhow()
+        .context("while loading")
+        .err()
+        .unwrap();
+    assert_eq!(
+        RenderOptions::default()
+            .kinds(true)
+            .color(true)
+            .render(&err),
+        [
+            "while loading",
+            "  caused by: while processing path \x1b[1;36m\"/a \\\"b\\\"\"\x1b[0m",
+            "    caused by: No such file or directory (os error 2) \x1b[2m[NotFound]\x1b[0m",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        RenderOptions::default()
+            .kinds(true)
+            .color(false)
+            .render(&err),
+        RenderOptions::default().kinds(true).render(&err)
+    );
+}
+
+#[test]
+fn dedupe() {
+    let err = anyhow!("denied")
+        .context(r#"while processing path "a""#)
+        .context("with mode 0o644")
+        .context(r#"while processing path "a""#);
+    assert_eq!(
+        RenderOptions::default().dedupe(true).render(&err),
+        [
+            r#"while processing path "a""#,
+            "  caused by: with mode 0o644",
+            "    caused by: denied",
+        ]
+        .join("\n")
+    );
+    assert_eq!(render_chain(&err).lines().count(), 4);
+}
+
+#[test_case(ErrorKind::NotFound => 66; "not found")]
+#[test_case(ErrorKind::PermissionDenied => 77; "permission denied")]
+#[test_case(ErrorKind::InvalidData => 5; "overridden")]
+#[test_case(ErrorKind::TimedOut => 74; "other io")]
+fn exit_code(kind: ErrorKind) -> u8 {
+    let err = anyhow::Error::new(std::io::Error::from(kind)).context("while testing");
+    ExitCodePolicy::default()
+        .with_kind(ErrorKind::InvalidData, 5)
+        .exit_code(&err)
+}
+
+#[test]
+fn exit_code_without_io_error() {
+    let policy = ExitCodePolicy::default();
+    assert_eq!(policy.exit_code(&anyhow!("plain")), 1);
+    assert_eq!(policy.with_default(70).exit_code(&anyhow!("plain")), 70);
+}
+
+#[test]
+fn hints_permission_denied() {
+    let res = Err::<(), _>(std::io::Error::from(ErrorKind::PermissionDenied));
+    let err = crate::context::with_path_context(res, Path::new("/etc/shadow"))
+        .err()
+        .unwrap();
+    assert_eq!(
+        RenderOptions::default().hints(true).render(&err),
+        [
+            r#"while processing path "/etc/shadow""#,
+            "  caused by: permission denied",
+            r#"hint: check ownership and permissions of "/etc/shadow""#,
+        ]
+        .join("\n")
+    );
+    assert_eq!(render_chain(&err).lines().count(), 2);
+}
+
+#[test]
+fn hints_permission_denied_two_paths() {
+    use crate::context::{with_two_path_context, Operation};
+
+    let res = Err::<(), _>(std::io::Error::from(ErrorKind::PermissionDenied));
+    let err = with_two_path_context(res, Operation::Copy, Path::new("a"), Path::new("b"))
+        .err()
+        .unwrap();
+    assert_eq!(
+        RenderOptions::default()
+            .hints(true)
+            .render(&err)
+            .lines()
+            .last(),
+        Some(if cfg!(feature = "compat-messages") {
+            r#"hint: check ownership and permissions of "a""#
+        } else {
+            r#"hint: check ownership and permissions of "a" and "b""#
+        })
+    );
+}
+
+#[test]
+fn hints_missing_program() {
+    use crate::CommandAnyhow;
+
+    let err = std::process::Command::new("/this/program/should/not/exist")
+        .current_dir("/")
+        .env("KEY", "value")
+        .arg("arg")
+        .spawn_anyhow()
+        .err()
+        .unwrap();
+    let rendered = RenderOptions::default().hints(true).render(&err);
+    assert_eq!(
+        rendered.lines().last(),
+        Some(r#"hint: is "/this/program/should/not/exist" installed and on PATH?"#)
+    );
+}
+
+#[test]
+fn hints_missing_current_dir() {
+    use crate::CommandAnyhow;
+
+    let err = std::process::Command::new("true")
+        .current_dir("/this/path/should/not/exist")
+        .spawn_anyhow()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.root_cause()
+            .downcast_ref::<std::io::Error>()
+            .map(std::io::Error::kind),
+        Some(ErrorKind::NotFound)
+    );
+    assert_eq!(
+        RenderOptions::default().hints(true).render(&err),
+        render_chain(&err)
+    );
+}
+
+#[test]
+fn hints_none() {
+    let err = anyhow!("unrelated").context("while processing path \"a\"");
+    assert_eq!(
+        RenderOptions::default().hints(true).render(&err),
+        render_chain(&err)
+    );
+}
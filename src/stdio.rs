@@ -0,0 +1,79 @@
+//! Terminal detection for the standard streams
+
+use std::fmt;
+use std::io::IsTerminal;
+
+#[cfg(all(feature = "password", target_family = "unix"))]
+mod password;
+
+#[cfg(all(feature = "password", target_family = "unix"))]
+pub use self::password::read_password_anyhow;
+
+/// One of the standard streams of the process
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Stream {
+    /// Standard input
+    Stdin,
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+impl Stream {
+    /// Return whether this stream is attached to a terminal
+    pub fn is_tty(self) -> bool {
+        match self {
+            Stream::Stdin => std::io::stdin().is_terminal(),
+            Stream::Stdout => std::io::stdout().is_terminal(),
+            Stream::Stderr => std::io::stderr().is_terminal(),
+        }
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Stream::Stdin => "stdin",
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        })
+    }
+}
+
+/// Return whether stdin is attached to a terminal
+pub fn is_tty_stdin() -> bool {
+    Stream::Stdin.is_tty()
+}
+
+/// Return whether stdout is attached to a terminal
+pub fn is_tty_stdout() -> bool {
+    Stream::Stdout.is_tty()
+}
+
+/// Return whether stderr is attached to a terminal
+pub fn is_tty_stderr() -> bool {
+    Stream::Stderr.is_tty()
+}
+
+/// Return an error naming `stream` unless it is attached to a terminal
+///
+/// Tools which must only run interactively, such as those prompting for confirmation, can call
+/// this up front rather than blocking on or misreading piped input.
+pub fn require_tty_anyhow(stream: Stream) -> anyhow::Result<()> {
+    require_tty(stream, stream.is_tty())
+}
+
+fn require_tty(stream: Stream, is_tty: bool) -> anyhow::Result<()> {
+    if is_tty {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} is not a terminal; this must be run interactively",
+            stream
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests;
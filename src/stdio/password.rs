@@ -0,0 +1,90 @@
+use anyhow::Context;
+use std::io::{BufRead, Write};
+use std::os::unix::io::RawFd;
+
+/// Print `prompt` to stderr and read a line from stdin without echoing it
+///
+/// Echo is disabled on the stdin terminal while reading and the previous terminal state is
+/// restored afterwards, including when reading fails. The trailing line ending is removed, and
+/// reaching the end of input before any line, as after Ctrl-D, is an error. Each
+/// failing terminal operation is named in the error context, so running with stdin redirected
+/// from a file reports that terminal attributes could not be read rather than leaking the input.
+pub fn read_password_anyhow(prompt: &str) -> anyhow::Result<String> {
+    read_password_from(
+        libc::STDIN_FILENO,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stderr(),
+        prompt,
+    )
+}
+
+pub(super) fn read_password_from<R, W>(
+    fd: RawFd,
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+) -> anyhow::Result<String>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut line = String::new();
+    {
+        let _echo = EchoDisabled::new(fd)?;
+        output
+            .write_all(prompt.as_bytes())
+            .and_then(|()| output.flush())
+            .context("while writing the password prompt")?;
+        let n = input
+            .read_line(&mut line)
+            .context("while reading the password")?;
+        if n == 0 {
+            anyhow::bail!("end of input while reading the password");
+        }
+    }
+    // The user's newline was not echoed, so end the prompt line ourselves.
+    output
+        .write_all(b"\n")
+        .context("while writing the password prompt")?;
+
+    let trimmed = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(trimmed);
+    Ok(line)
+}
+
+/// Disables echo on a terminal until dropped
+struct EchoDisabled {
+    fd: RawFd,
+    saved: libc::termios,
+}
+
+impl EchoDisabled {
+    fn new(fd: RawFd) -> anyhow::Result<Self> {
+        // SAFETY: `termios` is plain data, fully initialized by a successful `tcgetattr`.
+        let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+        check(unsafe { libc::tcgetattr(fd, &mut saved) })
+            .context("while reading terminal attributes of stdin")?;
+
+        let mut noecho = saved;
+        noecho.c_lflag &= !libc::ECHO;
+        check(unsafe { libc::tcsetattr(fd, libc::TCSANOW, &noecho) })
+            .context("while disabling terminal echo")?;
+
+        Ok(EchoDisabled { fd, saved })
+    }
+}
+
+impl Drop for EchoDisabled {
+    fn drop(&mut self) {
+        // Nothing useful can be done if restoring fails while unwinding or after an error.
+        let _ = unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.saved) };
+    }
+}
+
+fn check(ret: libc::c_int) -> std::io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::stdio::{require_tty, Stream};
+use crate::testutils::{err_str, stringify_error};
+use test_case::test_case;
+
+#[test_case(Stream::Stdin, true => Ok(()); "tty")]
+#[test_case(Stream::Stdin, false => err_str("stdin is not a terminal; this must be run interactively"); "stdin pipe")]
+#[test_case(Stream::Stdout, false => err_str("stdout is not a terminal; this must be run interactively"); "stdout pipe")]
+#[test_case(Stream::Stderr, false => err_str("stderr is not a terminal; this must be run interactively"); "stderr pipe")]
+fn require(stream: Stream, is_tty: bool) -> Result<(), String> {
+    stringify_error(require_tty(stream, is_tty))
+}
+
+#[cfg(all(feature = "password", target_family = "unix"))]
+#[test]
+fn read_password_not_a_terminal() -> anyhow::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = tempfile::tempfile()?;
+    let mut output = vec![];
+    let res = crate::stdio::password::read_password_from(
+        file.as_raw_fd(),
+        &mut &b"secret\n"[..],
+        &mut output,
+        "Password: ",
+    );
+    assert_eq!(
+        format!("{:#}", res.err().unwrap()),
+        "while reading terminal attributes of stdin: Inappropriate ioctl for device (os error 25)"
+    );
+    assert!(output.is_empty());
+    Ok(())
+}
+
+#[cfg(all(feature = "password", target_os = "linux"))]
+#[test_case(b"secret\n" => Ok("secret".to_string()); "line")]
+#[test_case(b"" => err_str("end of input while reading the password"); "end of input")]
+fn read_password_from_terminal(input: &[u8]) -> Result<String, String> {
+    let (mut master, mut slave) = (0, 0);
+    // SAFETY: `openpty` only writes the two descriptors when given null name and settings.
+    let ret = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    assert_eq!(ret, 0, "{}", std::io::Error::last_os_error());
+
+    let mut output = vec![];
+    let res = crate::stdio::password::read_password_from(
+        slave,
+        &mut &input[..],
+        &mut output,
+        "Password: ",
+    );
+    unsafe {
+        libc::close(slave);
+        libc::close(master);
+    }
+    stringify_error(res)
+}